@@ -1,11 +1,13 @@
-use casper_shorts_contracts::system::{MarketState, ONE_CENT, ONE_DOLLAR, ONE_TENTH_CENT};
+use casper_shorts_contracts::system::{
+    Linear, MarketState, Payoff, PayoffModel, ONE_CENT, ONE_DOLLAR, ONE_TENTH_CENT,
+};
 use odra::casper_types::U256;
 use plotly::{self, common::Mode, Plot, Scatter};
 
 // Draw short and long liquidity over time when price changes.
 pub fn draw_plot_1_price_goes_up() {
-    let (_rices, long_liquidity_inc, short_liquidity_inc) = symulate(true);
-    let (prices, long_liquidity, short_liquidity) = symulate(false);
+    let (_rices, long_liquidity_inc, short_liquidity_inc) = symulate(&Linear, true);
+    let (prices, long_liquidity, short_liquidity) = symulate(&Linear, false);
 
     // Create a new plot
     let mut plot = Plot::new();
@@ -46,6 +48,32 @@ pub fn draw_plot_1_price_goes_up() {
     plot.show();
 }
 
+// Draw long liquidity after a single price change for each payoff model.
+pub fn draw_plot_2_payoff_models() {
+    let models = [
+        ("linear", Payoff::Linear),
+        ("leveraged 2x", Payoff::LeveragedLinear { leverage: 2 }),
+        (
+            "capped 20%",
+            Payoff::Capped {
+                max_gain: 2_000,
+                max_loss: 2_000,
+            },
+        ),
+        ("power", Payoff::Power),
+    ];
+
+    let mut plot = Plot::new();
+    for (name, model) in models {
+        let (prices, long_liquidity) = payoff_curve(&model);
+        let trace = Scatter::new(prices, long_liquidity)
+            .mode(Mode::LinesMarkers)
+            .name(name);
+        plot.add_trace(trace);
+    }
+    plot.show();
+}
+
 fn to_usd(value: U256) -> f64 {
     value.as_u64() as f64 / ONE_DOLLAR as f64
 }
@@ -59,7 +87,31 @@ fn to_usd(value: U256) -> f64 {
 //     result
 // }
 
-fn symulate(incremental_mode: bool) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+// Long liquidity of a balanced market after the price moves from 0.01 USD
+// to each price between 0.005 and 0.02 USD.
+fn payoff_curve(model: &impl PayoffModel) -> (Vec<f64>, Vec<f64>) {
+    let initial_state = MarketState {
+        long_total_supply: U256::zero(),
+        short_total_supply: U256::zero(),
+        long_liquidity: U256::from(10_000),
+        short_liquidity: U256::from(10_000),
+        price: U256::from(ONE_CENT),
+    };
+
+    let mut prices = vec![];
+    let mut long_liquidity = vec![];
+    for i in 5..=20 {
+        let mut state = initial_state.clone();
+        let new_price = U256::from(ONE_TENTH_CENT) * U256::from(i);
+        state.on_price_change(model, new_price);
+
+        prices.push(to_usd(new_price));
+        long_liquidity.push(state.long_liquidity.as_u64() as f64);
+    }
+    (prices, long_liquidity)
+}
+
+fn symulate(model: &impl PayoffModel, incremental_mode: bool) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
     let mut state = MarketState {
         long_total_supply: U256::zero(),
         short_total_supply: U256::zero(),
@@ -76,7 +128,7 @@ fn symulate(incremental_mode: bool) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
         if incremental_mode {
             let old_price = state.price;
             let new_price = old_price + U256::from(ONE_TENTH_CENT);
-            state.on_price_goes_up(new_price);
+            state.on_price_goes_up(model, new_price);
 
             prices.push(to_usd(new_price));
             long_liquidity.push(state.long_liquidity.as_u64() as f64);
@@ -85,7 +137,7 @@ fn symulate(incremental_mode: bool) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
             let mut state = state.clone();
             let old_price = state.price;
             let new_price = old_price + U256::from(ONE_TENTH_CENT) * U256::from(i + 1);
-            state.on_price_goes_up(new_price);
+            state.on_price_goes_up(model, new_price);
 
             prices.push(to_usd(new_price));
             long_liquidity.push(state.long_liquidity.as_u64() as f64);
@@ -1,3 +1,4 @@
 fn main() {
     casper_shorts_charts::draw_plot_1_price_goes_up();
+    casper_shorts_charts::draw_plot_2_payoff_models();
 }
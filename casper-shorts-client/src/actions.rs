@@ -2,29 +2,54 @@ use std::thread;
 use std::time::Duration;
 
 use casper_shorts_contracts::config::Config;
-use casper_shorts_contracts::market::{MarketHostRef, MarketInitArgs};
+use casper_shorts_contracts::market::{Market, MarketInitArgs, TradePreview};
+use casper_shorts_contracts::multisig::{Multisig, MultisigInitArgs};
 use casper_shorts_contracts::price_data::PriceData;
-use casper_shorts_contracts::system::{ONE_CENT, ONE_DOLLAR};
-use casper_shorts_contracts::token_long::{TokenLongHostRef, TokenLongInitArgs};
-use casper_shorts_contracts::token_short::{TokenShortHostRef, TokenShortInitArgs};
-use casper_shorts_contracts::token_wcspr::{TokenWCSPRHostRef, TokenWCSPRInitArgs};
+use casper_shorts_contracts::registry::MarketRegistry;
+use casper_shorts_contracts::system::{Payoff, Side, ONE_CENT, ONE_DOLLAR};
+use casper_shorts_contracts::token_long::{TokenLong, TokenLongInitArgs};
+use casper_shorts_contracts::token_short::{TokenShort, TokenShortInitArgs};
+use casper_shorts_contracts::token_wcspr::{TokenWCSPR, TokenWCSPRInitArgs};
+use casper_shorts_contracts::version::STATE_VERSION;
 use odra::casper_types::U256;
 use odra::host::HostRef;
-use odra::host::{Deployer, HostEnv};
+use odra::host::{Deployer, HostEnv, HostRefLoader, NoArgs, OdraConfig};
+use odra::prelude::*;
 
 use crate::bots::runnner::Runner;
 use crate::bots::traders::random_trader::RandomTrader;
 use crate::deployed_contracts::{DeployedContracts, DeployedContractsToml};
-use crate::models::{BotMode, Recipient, SystemStats, Token, TransferOrder};
+use crate::migration::{self, AirdropProgress, MigrationPlan};
+use crate::models::{
+    BotMode, Recipient, SlippageTolerance, SystemStats, Token, TradingAction, TransferOrder,
+};
 use crate::{coinmarketcap, log};
 
+/// Installs a contract as an upgradable package, stored under
+/// `<name>_package_hash` in the deployer's account.
+struct Upgradable(String);
+
+impl OdraConfig for Upgradable {
+    fn package_hash(&self) -> String {
+        self.0.clone()
+    }
+
+    fn is_upgradable(&self) -> bool {
+        true
+    }
+
+    fn allow_key_override(&self) -> bool {
+        true
+    }
+}
+
 pub fn deploy_all() {
     DeployedContractsToml::handle_previous_version();
     let mut contracts = DeployedContractsToml::new();
     let env = odra_casper_livenet_env::env();
 
     env.set_gas(300_000_000_000);
-    let wcspr_token = TokenWCSPRHostRef::deploy(
+    let wcspr_token = TokenWCSPR::deploy_with_cfg(
         &env,
         TokenWCSPRInitArgs {
             name: "004_CS_CSPR".to_string(),
@@ -32,11 +57,12 @@ pub fn deploy_all() {
             decimals: 9,
             initial_supply: 1_000_000_000_000_000u64.into(),
         },
+        Upgradable("WCSPR".to_string()),
     );
     contracts.add_contract("WCSPR", wcspr_token.address());
 
     env.set_gas(300_000_000_000);
-    let short_token = TokenShortHostRef::deploy(
+    let short_token = TokenShort::deploy_with_cfg(
         &env,
         TokenShortInitArgs {
             name: "004_SHORT".to_string(),
@@ -44,11 +70,12 @@ pub fn deploy_all() {
             decimals: 9,
             initial_supply: 0u64.into(),
         },
+        Upgradable("SHORT".to_string()),
     );
     contracts.add_contract("SHORT", short_token.address());
 
     env.set_gas(300_000_000_000);
-    let long_token = TokenLongHostRef::deploy(
+    let long_token = TokenLong::deploy_with_cfg(
         &env,
         TokenLongInitArgs {
             name: "004_LONG".to_string(),
@@ -56,18 +83,22 @@ pub fn deploy_all() {
             decimals: 9,
             initial_supply: 0u64.into(),
         },
+        Upgradable("LONG".to_string()),
     );
     contracts.add_contract("LONG", long_token.address());
 
     env.set_gas(300_000_000_000);
-    let market = MarketHostRef::deploy(
+    let market = Market::deploy_with_cfg(
         &env,
         MarketInitArgs {
             last_price: PriceData {
                 price: ONE_CENT.into(),
-                timestamp: 0u64.into(),
+                timestamp: 0u64,
             },
+            expiry: None,
+            payoff: Payoff::Linear,
         },
+        Upgradable("Market".to_string()),
     );
     contracts.add_contract("Market", market.address());
 }
@@ -80,18 +111,18 @@ pub fn set_config() {
     env.set_gas(10_000_000_000);
     contracts
         .short_token
-        .change_security(vec![], vec![contracts.market.address().clone()], vec![]);
+        .change_security(vec![], vec![*contracts.market.address()], vec![]);
 
     env.set_gas(10_000_000_000);
     contracts
         .long_token
-        .change_security(vec![], vec![contracts.market.address().clone()], vec![]);
+        .change_security(vec![], vec![*contracts.market.address()], vec![]);
 
     let cfg = Config {
-        wcspr_token: contracts.wcspr_token.address().clone(),
-        short_token: contracts.short_token.address().clone(),
-        long_token: contracts.long_token.address().clone(),
-        market: contracts.market.address().clone(),
+        wcspr_token: *contracts.wcspr_token.address(),
+        short_token: *contracts.short_token.address(),
+        long_token: *contracts.long_token.address(),
+        market: *contracts.market.address(),
         fee_collector: env.get_account(0),
     };
 
@@ -101,6 +132,277 @@ pub fn set_config() {
     contracts.wcspr_token.set_config(cfg.clone());
 }
 
+pub fn deploy_market_registry() {
+    let mut contracts = DeployedContractsToml::load().unwrap();
+    let env = odra_casper_livenet_env::env();
+
+    env.set_gas(300_000_000_000);
+    let registry = MarketRegistry::deploy(&env, NoArgs);
+    contracts.add_contract("MarketRegistry", registry.address());
+}
+
+/// Deploys LONG, SHORT and Market contracts using the deployed WCSPR as the
+/// collateral, wires them and proposes the market on the WCSPR token. The
+/// market is dated if `expiry` is given.
+///
+/// The registry only lists markets the WCSPR routes deposits to, so the
+/// market is registered by `add_market` once the config delay has passed.
+pub fn deploy_market(price_feed: Option<Address>, expiry: Option<u64>, payoff: Payoff) {
+    let env = odra_casper_livenet_env::env();
+    let registry = DeployedContracts::market_registry(&env);
+    deploy_unrouted_market(registry.get_market_count() + 1, price_feed, expiry, payoff);
+}
+
+fn deploy_unrouted_market(
+    number: u32,
+    price_feed: Option<Address>,
+    expiry: Option<u64>,
+    payoff: Payoff,
+) {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let price_feed = price_feed.unwrap_or_else(|| env.get_account(0));
+    let prefix = format!("{:03}", number);
+
+    env.set_gas(300_000_000_000);
+    let mut short_token = TokenShort::deploy_with_cfg(
+        &env,
+        TokenShortInitArgs {
+            name: format!("{}_SHORT", prefix),
+            symbol: format!("{}_SHORT", prefix),
+            decimals: 9,
+            initial_supply: 0u64.into(),
+        },
+        Upgradable(format!("{}_SHORT", prefix)),
+    );
+
+    env.set_gas(300_000_000_000);
+    let mut long_token = TokenLong::deploy_with_cfg(
+        &env,
+        TokenLongInitArgs {
+            name: format!("{}_LONG", prefix),
+            symbol: format!("{}_LONG", prefix),
+            decimals: 9,
+            initial_supply: 0u64.into(),
+        },
+        Upgradable(format!("{}_LONG", prefix)),
+    );
+
+    env.set_gas(300_000_000_000);
+    let mut market = Market::deploy_with_cfg(
+        &env,
+        MarketInitArgs {
+            last_price: contracts.market.get_last_price(),
+            expiry,
+            payoff,
+        },
+        Upgradable(format!("{}_Market", prefix)),
+    );
+
+    // Make market minter of LONG and SHORT tokens.
+    env.set_gas(10_000_000_000);
+    short_token.change_security(vec![], vec![*market.address()], vec![]);
+    env.set_gas(10_000_000_000);
+    long_token.change_security(vec![], vec![*market.address()], vec![]);
+
+    let cfg = Config {
+        wcspr_token: *contracts.wcspr_token.address(),
+        short_token: *short_token.address(),
+        long_token: *long_token.address(),
+        market: *market.address(),
+        fee_collector: env.get_account(0),
+    };
+    env.set_gas(10_000_000_000);
+    market.set_config(cfg.clone());
+    env.set_gas(10_000_000_000);
+    long_token.set_config(cfg.clone());
+    env.set_gas(10_000_000_000);
+    short_token.set_config(cfg.clone());
+    env.set_gas(10_000_000_000);
+    market.set_keeper(&price_feed, true);
+
+    env.set_gas(10_000_000_000);
+    let eta = contracts.wcspr_token.propose_market(cfg);
+    log::info(format!(
+        "Deployed market {:?}, add it with `add-market` after {}",
+        market.address(),
+        eta
+    ));
+}
+
+/// Adds a market proposed by `deploy_market` to the WCSPR token once its ETA
+/// has passed and registers it.
+pub fn add_market(market: Address, price_feed: Option<Address>) {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let mut registry = DeployedContracts::market_registry(&env);
+    let price_feed = price_feed.unwrap_or_else(|| env.get_account(0));
+    let cfg = Market::load(&env, market)
+        .get_config()
+        .expect("Market not configured");
+
+    env.set_gas(10_000_000_000);
+    contracts.wcspr_token.add_market(market);
+    env.set_gas(10_000_000_000);
+    let id = registry.register_market(cfg, price_feed);
+    log::info(format!("Registered market {} at {:?}", id, market));
+}
+
+/// Deploys `count` dated markets expiring every `period` milliseconds after
+/// the last expiry of the registered markets, or after now if all expired.
+/// Each market still has to be added with `add_market`.
+pub fn roll_markets(count: u32, period: u64, price_feed: Option<Address>, payoff: Payoff) {
+    let env = odra_casper_livenet_env::env();
+    let registry = DeployedContracts::market_registry(&env);
+    let number = registry.get_market_count();
+    let start = registry
+        .get_markets()
+        .iter()
+        .filter_map(|entry| entry.expiry)
+        .fold(env.block_time(), u64::max);
+    for i in 1..=count as u64 {
+        let expiry = start + i * period;
+        log::info(format!("Deploying market expiring at {}", expiry));
+        deploy_unrouted_market(number + i as u32, price_feed, Some(expiry), payoff);
+    }
+}
+
+pub fn list_markets() {
+    let env = odra_casper_livenet_env::env();
+    let registry = DeployedContracts::market_registry(&env);
+    for entry in registry.get_markets() {
+        let expiry = entry
+            .expiry
+            .map_or("perpetual".to_string(), |expiry| expiry.to_string());
+        log::info(format!(
+            "Market {}: {:?}, LONG: {:?}, SHORT: {:?}, price feed: {:?}, expiry: {}",
+            entry.id,
+            entry.config.market,
+            entry.config.long_token,
+            entry.config.short_token,
+            entry.price_feed,
+            expiry
+        ));
+    }
+}
+
+/// Deploys a multisig and hands it the owner rights and the token admin
+/// badges of the deployed contracts. The deployer loses both.
+pub fn deploy_multisig(signers: Vec<Address>, threshold: u32) {
+    let mut deployed = DeployedContractsToml::load().unwrap();
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let deployer = env.get_account(0);
+
+    env.set_gas(300_000_000_000);
+    let multisig = Multisig::deploy(&env, MultisigInitArgs { signers, threshold });
+    deployed.add_contract("Multisig", multisig.address());
+    let owner = *multisig.address();
+
+    env.set_gas(10_000_000_000);
+    contracts.market.transfer_ownership(&owner);
+    env.set_gas(10_000_000_000);
+    contracts.long_token.transfer_ownership(&owner);
+    env.set_gas(10_000_000_000);
+    contracts.short_token.transfer_ownership(&owner);
+    env.set_gas(10_000_000_000);
+    contracts.wcspr_token.transfer_ownership(&owner);
+
+    env.set_gas(10_000_000_000);
+    contracts
+        .long_token
+        .change_security(vec![owner], vec![], vec![deployer]);
+    env.set_gas(10_000_000_000);
+    contracts
+        .short_token
+        .change_security(vec![owner], vec![], vec![deployer]);
+    env.set_gas(10_000_000_000);
+    contracts
+        .wcspr_token
+        .change_security(vec![owner], vec![], vec![deployer]);
+    log::info(format!("Multisig {:?} owns the contracts", owner));
+}
+
+/// Runs the state migrations of the deployed contracts once their packages
+/// got new code, and verifies that the market state and the token supplies
+/// are unchanged.
+pub fn upgrade() {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let state_before = contracts.market.get_market_state();
+    let supplies_before = token_supplies(&contracts);
+
+    if contracts.market.get_state_version() < STATE_VERSION {
+        env.set_gas(10_000_000_000);
+        contracts.market.migrate();
+    }
+    if contracts.long_token.get_state_version() < STATE_VERSION {
+        env.set_gas(10_000_000_000);
+        contracts.long_token.migrate();
+    }
+    if contracts.short_token.get_state_version() < STATE_VERSION {
+        env.set_gas(10_000_000_000);
+        contracts.short_token.migrate();
+    }
+    if contracts.wcspr_token.get_state_version() < STATE_VERSION {
+        env.set_gas(10_000_000_000);
+        contracts.wcspr_token.migrate();
+    }
+
+    let state_after = contracts.market.get_market_state();
+    assert_eq!(state_before, state_after, "Market state changed");
+    assert_eq!(
+        supplies_before,
+        token_supplies(&contracts),
+        "Token supplies changed"
+    );
+    log::info(format!(
+        "Contracts at state version {}, market state unchanged: {:?}",
+        STATE_VERSION, state_after
+    ));
+}
+
+/// Total supplies of LONG, SHORT and WCSPR.
+fn token_supplies(contracts: &DeployedContracts) -> [U256; 3] {
+    [
+        contracts.long_token.total_supply(),
+        contracts.short_token.total_supply(),
+        contracts.wcspr_token.total_supply(),
+    ]
+}
+
+/// Moves the holders of the deployment backed up in `backup` to the current
+/// deployment. Holders are read from `holders_file` if given, otherwise
+/// from the transfer history of the old tokens. Saves the airdrop progress
+/// and the reconciliation report next to the backup, so a failed run can be
+/// restarted.
+pub fn migrate(backup: &str, holders_file: Option<&str>, dry_run: bool) {
+    let env = odra_casper_livenet_env::env();
+    let old_toml = DeployedContractsToml::load_from(backup).expect("Backup not found");
+    let old = DeployedContracts::from_toml(&env, &old_toml);
+    let mut new = DeployedContracts::load(env.clone());
+
+    let mut holders = match holders_file {
+        Some(file_name) => migration::holders_from_file(file_name),
+        None => migration::holders_from_history(&env, &old),
+    };
+    // The deployer funds the airdrop and can't transfer to itself.
+    let deployer = env.get_account(0);
+    holders.retain(|holder| *holder != deployer);
+    log::info(format!(
+        "Migrating {} holders of the deployment from {}",
+        holders.len(),
+        old_toml.time()
+    ));
+
+    let plan = MigrationPlan::new(&old, &holders);
+    let mut progress = AirdropProgress::load(&format!("{}.airdrop", backup));
+    let report = migration::execute(&env, &mut new, &plan, &mut progress, backup, dry_run);
+    report.print();
+    report.save_at(&format!("{}.migration", backup));
+    assert!(report.is_reconciled(), "Airdrop doesn't match the plan");
+}
+
 pub fn update_price(dry_run: bool) {
     let env = odra_casper_livenet_env::env();
     let mut contracts = DeployedContracts::load(env.clone());
@@ -127,16 +429,62 @@ pub fn update_price(dry_run: bool) {
         return;
     }
 
+    // Timestamp the price with the latest block time, so keepers get rewarded
+    // for a fresh price.
+    let timestamp = env.block_time();
     env.set_gas(300_000_000);
     contracts.market.set_price(PriceData {
         price: new_price,
-        timestamp: 0,
+        timestamp,
     });
 
     let current_price = contracts.market.get_market_state().price;
     log::info(format!("New contract price: 0.0{} CSPR/USD", current_price));
 }
 
+/// Winds the market down at `price` CSPR/USD, or at the last price if not
+/// given.
+pub fn settle(price: Option<f64>) {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let last_price = contracts.market.get_last_price();
+    let price_data = match price {
+        Some(price) => PriceData {
+            price: U256::from((price * ONE_DOLLAR as f64).round() as u64),
+            timestamp: env.block_time(),
+        },
+        None => last_price,
+    };
+    env.set_gas(10_000_000_000);
+    contracts.market.settle(price_data);
+    log::info(format!(
+        "Market settled at 0.0{} CSPR/USD",
+        contracts.market.get_market_state().price
+    ));
+}
+
+/// Sweeps the liquidity left in the settled market to `recipient`.
+pub fn sweep(recipient: Address) {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    env.set_gas(10_000_000_000);
+    let amount = contracts.market.sweep(&recipient);
+    log::info(format!("Swept {} WCSPR to {:?}", amount, recipient));
+}
+
+/// Pauses or unpauses trading on the market.
+pub fn set_paused(paused: bool) {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    env.set_gas(10_000_000_000);
+    if paused {
+        contracts.market.pause();
+    } else {
+        contracts.market.unpause();
+    }
+    log::info(format!("Market paused: {}", contracts.market.is_paused()));
+}
+
 pub fn update_price_deamon(interval: Option<Duration>) {
     loop {
         update_price(false);
@@ -168,23 +516,13 @@ pub fn print_balances() {
     ));
 }
 
-pub fn go_long(amount: U256) {
-    let env = odra_casper_livenet_env::env();
-    let mut contracts = DeployedContracts::load(env.clone());
-    env.set_gas(10_000_000_000);
-    contracts
-        .wcspr_token
-        .transfer(contracts.long_token.address(), &amount)
-    // contracts.market.deposit_long(amount);
-}
-
 pub fn make_transfer(order: TransferOrder) {
     let env = odra_casper_livenet_env::env();
     let mut contracts = DeployedContracts::load(env.clone());
     let recipient = match order.recipient {
-        Recipient::WcsprContract => contracts.wcspr_token.address().clone(),
-        Recipient::ShortContract => contracts.short_token.address().clone(),
-        Recipient::LongContract => contracts.long_token.address().clone(),
+        Recipient::WcsprContract => *contracts.wcspr_token.address(),
+        Recipient::ShortContract => *contracts.short_token.address(),
+        Recipient::LongContract => *contracts.long_token.address(),
         Recipient::Address(address) => address,
     };
     let amount = order.amount;
@@ -200,12 +538,124 @@ pub fn make_transfer(order: TransferOrder) {
     }
 }
 
-pub fn run_bot(mode: BotMode, interval: Option<Duration>) {
+/// Asks the Market what the action of `account` would yield at the current
+/// state.
+pub fn preview_trade(
+    contracts: &DeployedContracts,
+    account: &Address,
+    action: &TradingAction,
+) -> TradePreview {
+    let amount = action.amount();
+    let market = &contracts.market;
+    match action {
+        TradingAction::GoLong { .. } => market.preview_deposit(Side::Long, amount),
+        TradingAction::GoShort { .. } => market.preview_deposit(Side::Short, amount),
+        TradingAction::StopLong { .. } => market.preview_withdraw(account, Side::Long, amount),
+        TradingAction::StopShort { .. } => market.preview_withdraw(account, Side::Short, amount),
+        TradingAction::Flip { from, .. } => market.preview_flip(account, *from, amount),
+    }
+}
+
+pub fn print_preview(action: &TradingAction) {
+    let env = odra_casper_livenet_env::env();
+    let contracts = DeployedContracts::load(env.clone());
+    let preview = preview_trade(&contracts, &env.get_account(0), action);
+    let unit = match action {
+        TradingAction::GoLong { .. } => "LONG",
+        TradingAction::GoShort { .. } => "SHORT",
+        TradingAction::StopLong { .. } | TradingAction::StopShort { .. } => "WCSPR",
+        TradingAction::Flip { from, .. } => match from {
+            Side::Long => "SHORT",
+            Side::Short => "LONG",
+        },
+    };
+    log::info(format!(
+        "Expected: {} {}, fee: {} WCSPR, early-exit fee: {} WCSPR",
+        preview.amount_out, unit, preview.fee, preview.early_exit_fee
+    ));
+}
+
+/// Protects the action with limits derived from its preview, so it
+/// reverts if the market moves beyond the tolerance before it executes.
+pub fn with_slippage(action: TradingAction, tolerance: SlippageTolerance) -> TradingAction {
+    let env = odra_casper_livenet_env::env();
+    let contracts = DeployedContracts::load(env.clone());
+    let preview = preview_trade(&contracts, &env.get_account(0), &action);
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    action.with_limits(tolerance.limits(preview.amount_out, now))
+}
+
+/// Executes a trading action. Flips and actions with slippage limits call the
+/// Market directly, the rest use the transfer interface.
+pub fn make_trade(action: TradingAction) {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let amount = action.amount();
+
+    if let TradingAction::Flip { from, .. } = action {
+        log::info(format!("Flipping {} {:?} tokens", amount, from));
+        env.set_gas(10_000_000_000);
+        contracts.market.flip(from, amount);
+        return;
+    }
+
+    let Some(limits) = action.limits() else {
+        if let Some(order) = action.to_transfer_order() {
+            make_transfer(order);
+        }
+        return;
+    };
+
+    log::info(format!("Executing {:?} with {:?}", action, limits));
+    env.set_gas(10_000_000_000);
+    match action {
+        TradingAction::GoLong { .. } => {
+            contracts
+                .market
+                .deposit_long_with_slippage(amount, limits.min_out, limits.deadline)
+        }
+        TradingAction::GoShort { .. } => {
+            contracts
+                .market
+                .deposit_short_with_slippage(amount, limits.min_out, limits.deadline)
+        }
+        TradingAction::StopLong { .. } => {
+            contracts
+                .market
+                .withdraw_long_with_slippage(amount, limits.min_out, limits.deadline)
+        }
+        TradingAction::StopShort { .. } => {
+            contracts
+                .market
+                .withdraw_short_with_slippage(amount, limits.min_out, limits.deadline)
+        }
+        TradingAction::Flip { .. } => unreachable!("Flips are handled above"),
+    };
+}
+
+/// Claims the tokens and collateral of trades settled at a price update.
+pub fn claim() {
+    let env = odra_casper_livenet_env::env();
+    let mut contracts = DeployedContracts::load(env.clone());
+    let claimable = contracts.market.get_claimable(&env.get_account(0));
+    if claimable.is_empty() {
+        log::info("Nothing to claim.");
+        return;
+    }
+    log::info(format!(
+        "Claiming {} LONG, {} SHORT and {} WCSPR",
+        claimable.long, claimable.short, claimable.collateral
+    ));
+    env.set_gas(10_000_000_000);
+    contracts.market.claim();
+}
+
+pub fn run_bot(mode: BotMode, interval: Option<Duration>, slippage: Option<SlippageTolerance>) {
     let mut runner = {
         match mode {
             BotMode::Random => {
                 let trader = RandomTrader::new();
-                Runner::new(trader)
+                Runner::new(trader, slippage)
             }
         }
     };
@@ -245,6 +695,16 @@ pub fn print_stats() {
     log::info(format!("SHORT: {}", stats.short_balance));
     log::info(format!("LONG: {}", stats.long_balance));
 
+    let position = contracts.market.get_position(&stats.account);
+    log::info(format!(
+        "SHORT value: {} WCSPR ({} bps of the pool)",
+        position.short_value, position.short_pool_share
+    ));
+    log::info(format!(
+        "LONG value: {} WCSPR ({} bps of the pool)",
+        position.long_value, position.long_pool_share
+    ));
+
     log::info("Market Conditions:");
     log::info(format!("Price: 0.0{} CSPR/USD", stats.market_state.price));
     log::info(format!(
@@ -2,17 +2,25 @@ use std::{thread, time::Duration};
 
 use odra::host::HostEnv;
 
-use crate::{actions, deployed_contracts::DeployedContracts, log, models::SystemStats};
+use crate::{
+    actions,
+    deployed_contracts::DeployedContracts,
+    log,
+    models::{SlippageTolerance, SystemStats},
+};
 
 use super::strategy::Strategy;
 
 pub struct Runner<T: Strategy> {
     strategy: T,
+    /// Tolerance the trades are protected with. Trades are sent unprotected
+    /// if not set.
+    slippage: Option<SlippageTolerance>,
 }
 
 impl<T: Strategy> Runner<T> {
-    pub fn new(strategy: T) -> Self {
-        Self { strategy }
+    pub fn new(strategy: T, slippage: Option<SlippageTolerance>) -> Self {
+        Self { strategy, slippage }
     }
 
     pub fn run_once(&mut self) {
@@ -25,10 +33,13 @@ impl<T: Strategy> Runner<T> {
             return;
         }
 
-        let action = action.unwrap();
+        let mut action = action.unwrap();
+        if let Some(tolerance) = self.slippage {
+            action = actions::with_slippage(action, tolerance);
+        }
         log::info(format!("Action: {:?}", action));
-        let transfer = action.to_transfer_order();
-        actions::make_transfer(transfer);
+        actions::print_preview(&action);
+        actions::make_trade(action);
     }
 
     pub fn run_forever(&mut self, interval: Duration) {
@@ -46,6 +57,12 @@ pub struct RunnerContext {
     pub env: HostEnv,
 }
 
+impl Default for RunnerContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RunnerContext {
     pub fn new() -> Self {
         let env = odra_casper_livenet_env::env();
@@ -7,6 +7,12 @@ use crate::{
 use rand::Rng;
 pub struct RandomTrader;
 
+impl Default for RandomTrader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RandomTrader {
     pub fn new() -> Self {
         Self
@@ -21,9 +27,11 @@ impl Strategy for RandomTrader {
         if !ctx.stats.wcspr_balance.is_zero() {
             options.push(TradingAction::GoLong {
                 amount: random(ctx.stats.wcspr_balance),
+                limits: None,
             });
             options.push(TradingAction::GoShort {
                 amount: random(ctx.stats.wcspr_balance),
+                limits: None,
             });
         }
 
@@ -31,6 +39,7 @@ impl Strategy for RandomTrader {
         if !ctx.stats.long_balance.is_zero() {
             options.push(TradingAction::StopLong {
                 amount: random(ctx.stats.long_balance),
+                limits: None,
             });
         }
 
@@ -38,6 +47,7 @@ impl Strategy for RandomTrader {
         if !ctx.stats.short_balance.is_zero() {
             options.push(TradingAction::StopShort {
                 amount: random(ctx.stats.short_balance),
+                limits: None,
             });
         }
 
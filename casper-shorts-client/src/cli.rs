@@ -1,5 +1,16 @@
-use crate::{actions, models::BotMode};
-use clap::{Parser, Subcommand};
+use std::str::FromStr;
+
+use casper_shorts_contracts::{
+    expiry::WEEK,
+    system::{Payoff, Side},
+};
+use odra::{casper_types::U256, prelude::*};
+
+use crate::{
+    actions,
+    models::{BotMode, SlippageTolerance, TradingAction},
+};
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Debug, Parser)]
 #[command(name = "casper-shorts-client")]
@@ -15,17 +26,119 @@ enum Commands {
     DeployContracts,
     /// Configures whitelists of all contracts
     SetConfig,
+    /// Deploys the registry of markets.
+    DeployMarketRegistry,
+    /// Deploys a market for the deployed WCSPR, wires it and proposes it on
+    /// the WCSPR token. Register it with `add-market` after the config
+    /// delay.
+    DeployMarket {
+        /// Account allowed to push prices. Defaults to the deployer.
+        #[arg(short, long)]
+        price_feed: Option<String>,
+        /// Expiry of a dated market, in milliseconds of the block time.
+        /// Defaults to a perpetual market.
+        #[arg(short, long)]
+        expiry: Option<u64>,
+        /// Payoff model: `linear`, `leveraged:<leverage>`,
+        /// `capped:<max gain bps>:<max loss bps>` or `power`.
+        #[arg(long, default_value = "linear")]
+        payoff: String,
+    },
+    /// Adds a market proposed by `deploy-market` or `roll-markets` to the
+    /// WCSPR token and registers it in the market registry.
+    AddMarket {
+        /// Address of the Market contract.
+        market: String,
+        /// Account allowed to push prices. Defaults to the deployer.
+        #[arg(short, long)]
+        price_feed: Option<String>,
+    },
+    /// Deploys a series of dated markets, each expiring a period after the
+    /// previous one, starting from the last expiry of the registered
+    /// markets.
+    RollMarkets {
+        /// Number of markets to deploy.
+        count: u32,
+        /// Time between the expiries, in milliseconds. Defaults to a week.
+        #[arg(long, default_value_t = WEEK)]
+        period: u64,
+        /// Account allowed to push prices. Defaults to the deployer.
+        #[arg(short, long)]
+        price_feed: Option<String>,
+        /// Payoff model: `linear`, `leveraged:<leverage>`,
+        /// `capped:<max gain bps>:<max loss bps>` or `power`.
+        #[arg(long, default_value = "linear")]
+        payoff: String,
+    },
+    /// Lists the markets registered in the market registry.
+    ListMarkets,
+    /// Migrates the state of the deployed contracts to the current layout
+    /// after new code was added to their packages, and checks the market
+    /// state and the token supplies didn't change.
+    Upgrade,
+    /// Airdrops the WCSPR value of the balances held on a previous
+    /// deployment to its holders on the current deployment.
+    Migrate {
+        /// Backup of the previous deployment, e.g.
+        /// `deployed_contracts.toml.<date>`.
+        backup: String,
+        /// File with one holder address per line. Defaults to the holders
+        /// found in the transfer history of the old tokens.
+        #[arg(long)]
+        holders: Option<String>,
+        /// Only report the plan.
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+    /// Deploys an M-of-N multisig and makes it the owner and the token admin
+    /// of the Market and the tokens.
+    DeployMultisig {
+        /// Threshold of approvals needed to execute a call.
+        threshold: u32,
+        /// Signer addresses.
+        #[arg(required = true)]
+        signers: Vec<String>,
+    },
     /// Update price.
     UpdatePrice {
         #[arg(short, long)]
         dry_run: bool,
     },
+    /// Winds the market down: the price becomes final and LONG and SHORT
+    /// redeem at the settled liquidity.
+    Settle {
+        /// Final price in CSPR/USD. Defaults to the last price.
+        #[arg(short, long)]
+        price: Option<f64>,
+    },
+    /// Sweeps the liquidity left unredeemed after the grace period of a
+    /// settled or expired market.
+    Sweep {
+        recipient: String,
+    },
+    /// Stops deposits, withdrawals, flips and order executions in an
+    /// emergency.
+    Pause,
+    /// Lets trading resume after a pause.
+    Unpause,
     UpdatePriceDeamon {
         interval_seconds: Option<u64>,
     },
     PrintBalances,
-    GoLong,
+    GoLong {
+        #[command(flatten)]
+        slippage: SlippageArgs,
+    },
     PrintStats,
+    /// Exchanges LONG tokens for SHORT tokens or the other way around.
+    Flip {
+        /// Side to flip from: `long` or `short`.
+        from: String,
+        /// Amount of tokens, in motes.
+        amount: u64,
+    },
+    /// Claims tokens and collateral of trades settled by the Market.
+    Claim,
     TransferWCSPR {
         amount: f64,
         recipient: String,
@@ -45,31 +158,150 @@ pub enum RunBotCommands {
     Random {
         #[arg(short, long)]
         interval_seconds: Option<u64>,
+        #[command(flatten)]
+        slippage: SlippageArgs,
     },
 }
 
+/// Slippage protection of the trades sent by a command.
+#[derive(Debug, Args)]
+pub struct SlippageArgs {
+    /// Largest shortfall from the previewed amount, in basis points. Trades
+    /// are sent without slippage protection if not set.
+    #[arg(long)]
+    max_slippage: Option<u64>,
+    /// Time a trade stays valid after it is sent, in seconds.
+    #[arg(long, default_value_t = 300)]
+    validity_seconds: u64,
+}
+
+impl SlippageArgs {
+    fn tolerance(&self) -> Option<SlippageTolerance> {
+        self.max_slippage.map(|max_slippage| SlippageTolerance {
+            max_slippage,
+            validity: self.validity_seconds * 1000,
+        })
+    }
+}
+
 pub fn parse() {
     match Cli::parse().command {
         Commands::DeployContracts => actions::deploy_all(),
         Commands::SetConfig => actions::set_config(),
+        Commands::DeployMarketRegistry => actions::deploy_market_registry(),
+        Commands::DeployMarket {
+            price_feed,
+            expiry,
+            payoff,
+        } => actions::deploy_market(
+            price_feed.map(|address| address_of(&address)),
+            expiry,
+            parse_payoff(&payoff),
+        ),
+        Commands::AddMarket { market, price_feed } => actions::add_market(
+            address_of(&market),
+            price_feed.map(|address| address_of(&address)),
+        ),
+        Commands::RollMarkets {
+            count,
+            period,
+            price_feed,
+            payoff,
+        } => actions::roll_markets(
+            count,
+            period,
+            price_feed.map(|address| address_of(&address)),
+            parse_payoff(&payoff),
+        ),
+        Commands::ListMarkets => actions::list_markets(),
+        Commands::Upgrade => actions::upgrade(),
+        Commands::Migrate {
+            backup,
+            holders,
+            dry_run,
+        } => actions::migrate(&backup, holders.as_deref(), dry_run),
+        Commands::DeployMultisig { threshold, signers } => actions::deploy_multisig(
+            signers
+                .iter()
+                .map(|address| Address::from_str(address).expect("Invalid address"))
+                .collect(),
+            threshold,
+        ),
         Commands::UpdatePrice { dry_run } => actions::update_price(dry_run),
+        Commands::Settle { price } => actions::settle(price),
+        Commands::Sweep { recipient } => actions::sweep(address_of(&recipient)),
+        Commands::Pause => actions::set_paused(true),
+        Commands::Unpause => actions::set_paused(false),
         Commands::UpdatePriceDeamon { interval_seconds } => {
             actions::update_price_deamon(duration(interval_seconds))
         }
         Commands::PrintBalances => actions::print_balances(),
-        Commands::GoLong => actions::go_long(1_000_000_000.into()),
+        Commands::GoLong { slippage } => {
+            let mut action = TradingAction::GoLong {
+                amount: U256::from(1_000_000_000u64),
+                limits: None,
+            };
+            if let Some(tolerance) = slippage.tolerance() {
+                action = actions::with_slippage(action, tolerance);
+            }
+            actions::print_preview(&action);
+            actions::make_trade(action);
+        }
         Commands::TransferWCSPR { .. } => {
             panic!("Not implemented")
         }
         Commands::RunBot(run_bot) => match run_bot.run_bots_commands {
-            RunBotCommands::Random { interval_seconds } => {
-                actions::run_bot(BotMode::Random, duration(interval_seconds))
-            }
+            RunBotCommands::Random {
+                interval_seconds,
+                slippage,
+            } => actions::run_bot(
+                BotMode::Random,
+                duration(interval_seconds),
+                slippage.tolerance(),
+            ),
         },
         Commands::PrintStats => actions::print_stats(),
+        Commands::Claim => actions::claim(),
+        Commands::Flip { from, amount } => {
+            let action = TradingAction::Flip {
+                from: side(&from),
+                amount: U256::from(amount),
+            };
+            actions::print_preview(&action);
+            actions::make_trade(action);
+        }
     }
 }
 
+fn address_of(address: &str) -> Address {
+    Address::from_str(address).expect("Invalid address")
+}
+
 fn duration(seconds: Option<u64>) -> Option<std::time::Duration> {
-    seconds.map(|s| std::time::Duration::from_secs(s))
+    seconds.map(std::time::Duration::from_secs)
+}
+
+fn parse_payoff(name: &str) -> Payoff {
+    let parts: Vec<&str> = name.split(':').collect();
+    let number = |part: &str| part.parse::<u64>().expect("Invalid payoff parameter");
+    match parts.as_slice() {
+        ["linear"] => Payoff::Linear,
+        ["leveraged", leverage] => Payoff::LeveragedLinear {
+            leverage: number(leverage),
+        },
+        ["capped", max_gain, max_loss] => Payoff::Capped {
+            max_gain: number(max_gain),
+            max_loss: number(max_loss),
+        },
+        ["power"] => Payoff::Power,
+        _ => panic!("Unknown payoff: {}", name),
+    }
+}
+
+fn side(name: &str) -> Side {
+    match name {
+        "long" => Side::Long,
+        "short" => Side::Short,
+        _ => panic!("Unknown side: {}", name),
+    }
 }
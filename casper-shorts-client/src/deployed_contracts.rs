@@ -1,13 +1,16 @@
 use std::{fs::File, io::Write, str::FromStr};
 
 use casper_shorts_contracts::{
-    market::MarketHostRef, token_long::TokenLongHostRef, token_short::TokenShortHostRef,
-    token_wcspr::TokenWCSPRHostRef,
+    market::{Market, MarketHostRef},
+    registry::{MarketRegistry, MarketRegistryHostRef},
+    token_long::{TokenLong, TokenLongHostRef},
+    token_short::{TokenShort, TokenShortHostRef},
+    token_wcspr::{TokenWCSPR, TokenWCSPRHostRef},
 };
 use chrono::{DateTime, SecondsFormat, Utc};
 use odra::{
     host::{HostEnv, HostRefLoader},
-    Address,
+    prelude::*,
 };
 use serde_derive::{Deserialize, Serialize};
 
@@ -20,6 +23,12 @@ pub struct DeployedContractsToml {
     pub contracts: Vec<Contract>,
 }
 
+impl Default for DeployedContractsToml {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DeployedContractsToml {
     /// Create new instance.
     pub fn new() -> Self {
@@ -66,7 +75,13 @@ impl DeployedContractsToml {
 
     /// Load from the file.
     pub fn load() -> Option<Self> {
-        std::fs::read_to_string(DEPLOYED_CONTRACTS_FILE)
+        Self::load_from(DEPLOYED_CONTRACTS_FILE)
+    }
+
+    /// Load from the file at the given path, e.g. a backup of a previous
+    /// deployment.
+    pub fn load_from(file_name: &str) -> Option<Self> {
+        std::fs::read_to_string(file_name)
             .ok()
             .map(|s| toml::from_str(&s).unwrap())
     }
@@ -103,12 +118,23 @@ pub struct DeployedContracts {
 
 impl DeployedContracts {
     pub fn load(env: HostEnv) -> Self {
-        let contracts = DeployedContractsToml::load().unwrap();
+        Self::from_toml(&env, &DeployedContractsToml::load().unwrap())
+    }
+
+    /// Loads the contracts listed in the given file.
+    pub fn from_toml(env: &HostEnv, contracts: &DeployedContractsToml) -> Self {
         Self {
-            wcspr_token: TokenWCSPRHostRef::load(&env, contracts.address("WCSPR").unwrap()),
-            short_token: TokenShortHostRef::load(&env, contracts.address("SHORT").unwrap()),
-            long_token: TokenLongHostRef::load(&env, contracts.address("LONG").unwrap()),
-            market: MarketHostRef::load(&env, contracts.address("Market").unwrap()),
+            wcspr_token: TokenWCSPR::load(env, contracts.address("WCSPR").unwrap()),
+            short_token: TokenShort::load(env, contracts.address("SHORT").unwrap()),
+            long_token: TokenLong::load(env, contracts.address("LONG").unwrap()),
+            market: Market::load(env, contracts.address("Market").unwrap()),
         }
     }
+    /// Loads the market registry, deployed with `deploy-market-registry`.
+    pub fn market_registry(env: &HostEnv) -> MarketRegistryHostRef {
+        let address = DeployedContractsToml::load()
+            .and_then(|contracts| contracts.address("MarketRegistry"))
+            .expect("MarketRegistry not deployed");
+        MarketRegistry::load(env, address)
+    }
 }
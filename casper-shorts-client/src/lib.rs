@@ -3,6 +3,7 @@ pub mod bots;
 pub mod cli;
 pub mod coinmarketcap;
 pub mod deployed_contracts;
+pub mod migration;
 pub mod models;
 
 mod log {
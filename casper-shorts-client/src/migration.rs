@@ -0,0 +1,483 @@
+//! Moves the users of a previous deployment to the current one.
+//!
+//! Every holder of the old contracts gets the WCSPR value of their old
+//! WCSPR, LONG and SHORT balances airdropped from the deployer's WCSPR on
+//! the current deployment. LONG and SHORT are valued at the old market's
+//! last state, as a withdrawal from it would pay them: the trading fee is
+//! taken from their LONG and SHORT value and left in the old market.
+//!
+//! The progress of the airdrop is saved after every transfer, so a failed
+//! run can be restarted without paying anyone twice.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{File, OpenOptions},
+    io::Write,
+    str::FromStr,
+};
+
+use casper_shorts_contracts::{market::split_fee, system::MarketState};
+use odra::{
+    casper_event_standard::try_full_name_from_bytes,
+    casper_types::{bytesrepr::FromBytes, U256},
+    host::{HostEnv, HostRef},
+    prelude::*,
+};
+use odra_modules::cep18::events::{Mint, Transfer, TransferFrom};
+use serde_derive::Serialize;
+
+use crate::{deployed_contracts::DeployedContracts, log};
+
+/// Balances of a holder on the old deployment.
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub holder: Address,
+    pub wcspr: U256,
+    pub long: U256,
+    pub short: U256,
+    /// WCSPR to airdrop on the new deployment.
+    pub value: U256,
+    /// Trading fee taken from the LONG and SHORT value.
+    pub fee: U256,
+}
+
+impl Holding {
+    /// Values the balances of `holder` at the old market's `state`.
+    pub fn new(holder: Address, wcspr: U256, long: U256, short: U256, state: &MarketState) -> Self {
+        let (positions, fee) = split_fee(
+            share_of(long, state.long_total_supply, state.long_liquidity)
+                + share_of(short, state.short_total_supply, state.short_liquidity),
+        );
+        Self {
+            holder,
+            wcspr,
+            long,
+            short,
+            value: wcspr + positions,
+            fee,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MigrationPlan {
+    pub holdings: Vec<Holding>,
+    /// State of the old market the LONG and SHORT balances are valued at.
+    pub market_state: MarketState,
+}
+
+impl MigrationPlan {
+    /// Values the balances of the holders on the old deployment. Holders
+    /// with nothing to redeem are left out.
+    pub fn new(old: &DeployedContracts, holders: &[Address]) -> Self {
+        let market_state = old.market.get_market_state();
+        let holdings = holders
+            .iter()
+            .map(|holder| {
+                Holding::new(
+                    *holder,
+                    old.wcspr_token.balance_of(holder),
+                    old.long_token.balance_of(holder),
+                    old.short_token.balance_of(holder),
+                    &market_state,
+                )
+            })
+            .filter(|holding| !holding.value.is_zero())
+            .collect();
+        Self {
+            holdings,
+            market_state,
+        }
+    }
+
+    pub fn total(&self) -> U256 {
+        self.holdings
+            .iter()
+            .fold(U256::zero(), |total, holding| total + holding.value)
+    }
+
+    /// WCSPR still to airdrop to the holders missing from `progress`.
+    pub fn remaining(&self, progress: &AirdropProgress) -> U256 {
+        self.holdings
+            .iter()
+            .filter(|holding| progress.airdropped_to(&holding.holder).is_none())
+            .fold(U256::zero(), |total, holding| total + holding.value)
+    }
+
+    pub fn fees(&self) -> U256 {
+        self.holdings
+            .iter()
+            .fold(U256::zero(), |total, holding| total + holding.fee)
+    }
+
+    /// Market liquidity not claimed by any of the planned holders nor taken
+    /// as their fees: rounding dust or LONG and SHORT held by accounts
+    /// missing from the plan.
+    pub fn unclaimed_liquidity(&self) -> U256 {
+        let claimed = self.holdings.iter().fold(U256::zero(), |total, holding| {
+            total + holding.value - holding.wcspr + holding.fee
+        });
+        let liquidity = self.market_state.long_liquidity + self.market_state.short_liquidity;
+        liquidity.saturating_sub(claimed)
+    }
+}
+
+/// Part of the side's liquidity the balance redeems.
+fn share_of(balance: U256, total_supply: U256, liquidity: U256) -> U256 {
+    if total_supply.is_zero() {
+        return U256::zero();
+    }
+    balance * liquidity / total_supply
+}
+
+/// Accounts that received WCSPR, LONG or SHORT on the old deployment, read
+/// from the Mint, Transfer and TransferFrom events of its tokens. Contracts
+/// are left out.
+pub fn holders_from_history(env: &HostEnv, old: &DeployedContracts) -> Vec<Address> {
+    let mut holders = BTreeSet::new();
+    let tokens = [
+        *old.wcspr_token.address(),
+        *old.long_token.address(),
+        *old.short_token.address(),
+    ];
+    for token in tokens.iter() {
+        for bytes in env.events(token) {
+            match try_full_name_from_bytes(&bytes).as_deref() {
+                Ok("event_Mint") => {
+                    let (event, _) = Mint::from_bytes(&bytes).unwrap();
+                    holders.insert(event.recipient);
+                }
+                Ok("event_Transfer") => {
+                    let (event, _) = Transfer::from_bytes(&bytes).unwrap();
+                    holders.insert(event.sender);
+                    holders.insert(event.recipient);
+                }
+                Ok("event_TransferFrom") => {
+                    let (event, _) = TransferFrom::from_bytes(&bytes).unwrap();
+                    holders.insert(event.owner);
+                    holders.insert(event.recipient);
+                }
+                _ => {}
+            }
+        }
+    }
+    holders
+        .into_iter()
+        .filter(|holder| !holder.is_contract())
+        .collect()
+}
+
+/// Reads holders from a file with one address per line. Empty lines and
+/// lines starting with `#` are skipped.
+pub fn holders_from_file(file_name: &str) -> Vec<Address> {
+    std::fs::read_to_string(file_name)
+        .unwrap()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| Address::from_str(line).expect("Invalid address"))
+        .collect()
+}
+
+/// Airdrops already made, saved as one `holder amount` line per transfer.
+pub struct AirdropProgress {
+    file_name: String,
+    airdropped: BTreeMap<String, U256>,
+}
+
+impl AirdropProgress {
+    /// Loads the progress saved in `file_name`, if any.
+    pub fn load(file_name: &str) -> Self {
+        let airdropped = std::fs::read_to_string(file_name)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(holder, amount)| {
+                let amount = U256::from_dec_str(amount).expect("Invalid airdrop progress");
+                (holder.to_string(), amount)
+            })
+            .collect();
+        Self {
+            file_name: file_name.to_string(),
+            airdropped,
+        }
+    }
+
+    pub fn airdropped_to(&self, holder: &Address) -> Option<U256> {
+        self.airdropped.get(&holder.to_string()).copied()
+    }
+
+    /// Saves the transfer to disk before the next one is made. A run
+    /// interrupted between a transfer and its record pays that holder again
+    /// on restart, so check the last holder of the file against the chain.
+    pub fn record(&mut self, holder: &Address, amount: U256) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_name)
+            .unwrap();
+        let holder = holder.to_string();
+        writeln!(file, "{} {}", holder, amount).unwrap();
+        file.sync_all().unwrap();
+        self.airdropped.insert(holder, amount);
+    }
+}
+
+/// Outcome of the airdrop to one holder.
+#[derive(Debug, Serialize)]
+pub struct ReconciliationEntry {
+    pub holder: String,
+    pub old_wcspr: String,
+    pub old_long: String,
+    pub old_short: String,
+    pub planned: String,
+    pub fee: String,
+    pub received: String,
+    /// Received on an earlier run.
+    pub resumed: bool,
+    pub reconciled: bool,
+}
+
+impl ReconciliationEntry {
+    /// Compares the `received` WCSPR with the planned value of `holding`.
+    /// `earlier` is what an earlier run recorded for the holder. Unpaid
+    /// holders of a dry run are reconciled, as nothing was sent yet.
+    pub fn new(holding: &Holding, earlier: Option<U256>, received: U256, dry_run: bool) -> Self {
+        Self {
+            holder: holding.holder.to_string(),
+            old_wcspr: holding.wcspr.to_string(),
+            old_long: holding.long.to_string(),
+            old_short: holding.short.to_string(),
+            planned: holding.value.to_string(),
+            fee: holding.fee.to_string(),
+            received: received.to_string(),
+            resumed: earlier.is_some(),
+            reconciled: (dry_run && earlier.is_none()) || received == holding.value,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationReport {
+    pub backup: String,
+    pub dry_run: bool,
+    pub planned_total: String,
+    pub received_total: String,
+    pub fees_total: String,
+    pub unclaimed_liquidity: String,
+    pub entries: Vec<ReconciliationEntry>,
+}
+
+impl ReconciliationReport {
+    pub fn is_reconciled(&self) -> bool {
+        self.entries.iter().all(|entry| entry.reconciled)
+    }
+
+    pub fn print(&self) {
+        for entry in self.entries.iter() {
+            log::info(format!(
+                "{}: {} WCSPR, {} LONG, {} SHORT -> planned {} after {} fee, received {}{}{}",
+                entry.holder,
+                entry.old_wcspr,
+                entry.old_long,
+                entry.old_short,
+                entry.planned,
+                entry.fee,
+                entry.received,
+                if entry.resumed { " earlier" } else { "" },
+                if entry.reconciled { "" } else { " MISMATCH" }
+            ));
+        }
+        log::info(format!(
+            "Planned {} WCSPR after {} WCSPR fees, received {} WCSPR, unclaimed liquidity {} WCSPR",
+            self.planned_total, self.fees_total, self.received_total, self.unclaimed_liquidity
+        ));
+    }
+
+    /// Saves the report as TOML.
+    pub fn save_at(&self, file_name: &str) {
+        let content = toml::to_string_pretty(&self).unwrap();
+        let mut file = File::create(file_name).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+}
+
+/// Airdrops the planned WCSPR from the deployer on the new deployment and
+/// checks every holder received their value. Holders found in `progress`
+/// were paid on an earlier run and are skipped. A dry run only reports the
+/// plan.
+pub fn execute(
+    env: &HostEnv,
+    new: &mut DeployedContracts,
+    plan: &MigrationPlan,
+    progress: &mut AirdropProgress,
+    backup: &str,
+    dry_run: bool,
+) -> ReconciliationReport {
+    let deployer = env.get_account(0);
+    let remaining = plan.remaining(progress);
+    let available = new.wcspr_token.balance_of(&deployer);
+    if !dry_run && available < remaining {
+        panic!(
+            "Not enough WCSPR to airdrop: {} needed, {} available",
+            remaining, available
+        );
+    }
+
+    let mut received_total = U256::zero();
+    let mut entries = Vec::new();
+    for holding in plan.holdings.iter() {
+        let earlier = progress.airdropped_to(&holding.holder);
+        let received = match earlier {
+            Some(received) => received,
+            None if dry_run => U256::zero(),
+            None => {
+                let before = new.wcspr_token.balance_of(&holding.holder);
+                env.set_gas(10_000_000_000);
+                new.wcspr_token.transfer(&holding.holder, &holding.value);
+                let received = new.wcspr_token.balance_of(&holding.holder) - before;
+                progress.record(&holding.holder, received);
+                received
+            }
+        };
+        received_total += received;
+        entries.push(ReconciliationEntry::new(
+            holding, earlier, received, dry_run,
+        ));
+    }
+
+    ReconciliationReport {
+        backup: backup.to_string(),
+        dry_run,
+        planned_total: plan.total().to_string(),
+        received_total: received_total.to_string(),
+        fees_total: plan.fees().to_string(),
+        unclaimed_liquidity: plan.unclaimed_liquidity().to_string(),
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use odra::casper_types::account::AccountHash;
+
+    use super::*;
+
+    fn account(id: u8) -> Address {
+        Address::Account(AccountHash::new([id; 32]))
+    }
+
+    /// 100 LONG worth 200 WCSPR and 50 SHORT worth 50 WCSPR.
+    fn state() -> MarketState {
+        MarketState {
+            long_total_supply: U256::from(100_000),
+            short_total_supply: U256::from(50_000),
+            long_liquidity: U256::from(200_000),
+            short_liquidity: U256::from(50_000),
+            price: U256::from(100),
+        }
+    }
+
+    fn plan() -> MigrationPlan {
+        let state = state();
+        MigrationPlan {
+            holdings: vec![
+                Holding::new(
+                    account(1),
+                    U256::from(1_000),
+                    U256::from(10_000),
+                    U256::zero(),
+                    &state,
+                ),
+                Holding::new(
+                    account(2),
+                    U256::zero(),
+                    U256::zero(),
+                    U256::from(20_000),
+                    &state,
+                ),
+            ],
+            market_state: state,
+        }
+    }
+
+    #[test]
+    fn positions_are_valued_after_the_fee() {
+        let holding = Holding::new(
+            account(1),
+            U256::from(1_000),
+            U256::from(10_000),
+            U256::from(20_000),
+            &state(),
+        );
+        // 20000 WCSPR of LONG and 20000 of SHORT pay the 0.5% fee. The
+        // WCSPR balance pays none.
+        assert_eq!(holding.fee, U256::from(200));
+        assert_eq!(holding.value, U256::from(1_000 + 40_000 - 200));
+    }
+
+    #[test]
+    fn unclaimed_liquidity_excludes_values_and_fees() {
+        let plan = plan();
+        assert_eq!(plan.total(), U256::from(1_000 + 19_900 + 19_900));
+        assert_eq!(plan.fees(), U256::from(200));
+        assert_eq!(plan.unclaimed_liquidity(), U256::from(250_000 - 40_000));
+    }
+
+    #[test]
+    fn a_partial_progress_file_resumes_the_airdrop() {
+        let file_name = std::env::temp_dir()
+            .join(format!("airdrop-progress-{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = std::fs::remove_file(&file_name);
+        let plan = plan();
+
+        let mut progress = AirdropProgress::load(&file_name);
+        assert_eq!(plan.remaining(&progress), plan.total());
+        progress.record(&account(1), U256::from(20_900));
+
+        let resumed = AirdropProgress::load(&file_name);
+        std::fs::remove_file(&file_name).unwrap();
+        assert_eq!(resumed.airdropped_to(&account(1)), Some(U256::from(20_900)));
+        assert_eq!(resumed.airdropped_to(&account(2)), None);
+        assert_eq!(plan.remaining(&resumed), U256::from(19_900));
+    }
+
+    #[test]
+    fn a_short_airdrop_is_a_mismatch() {
+        let plan = plan();
+        let (paid, short) = (&plan.holdings[0], &plan.holdings[1]);
+        let report = |entries| ReconciliationReport {
+            backup: String::new(),
+            dry_run: false,
+            planned_total: String::new(),
+            received_total: String::new(),
+            fees_total: String::new(),
+            unclaimed_liquidity: String::new(),
+            entries,
+        };
+
+        let entries = vec![
+            ReconciliationEntry::new(paid, Some(paid.value), paid.value, false),
+            ReconciliationEntry::new(short, None, short.value, false),
+        ];
+        assert!(report(entries).is_reconciled());
+
+        let entries = vec![
+            ReconciliationEntry::new(paid, Some(paid.value), paid.value, false),
+            ReconciliationEntry::new(short, None, short.value - 1, false),
+        ];
+        let report = report(entries);
+        assert!(report.entries[0].resumed && report.entries[0].reconciled);
+        assert!(!report.entries[1].reconciled);
+        assert!(!report.is_reconciled());
+    }
+
+    #[test]
+    fn unpaid_holders_of_a_dry_run_are_reconciled() {
+        let plan = plan();
+        let entry = ReconciliationEntry::new(&plan.holdings[0], None, U256::zero(), true);
+        assert!(entry.reconciled);
+        assert!(!entry.resumed);
+    }
+}
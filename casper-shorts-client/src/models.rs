@@ -1,7 +1,7 @@
 // Structs and enums used in the client.
 
-use casper_shorts_contracts::system::MarketState;
-use odra::{casper_types::U256, Address};
+use casper_shorts_contracts::system::{MarketState, Side, BASIS_POINTS};
+use odra::{casper_types::U256, prelude::*};
 
 #[derive(Debug)]
 pub enum Token {
@@ -25,38 +25,121 @@ pub struct TransferOrder {
     pub amount: U256,
 }
 
+/// Protects a trade against price moves between sending and execution.
+#[derive(Debug, Clone, Copy)]
+pub struct SlippageLimits {
+    /// Minimum amount of tokens (deposits) or WCSPR (withdrawals) to receive.
+    pub min_out: U256,
+    /// Block time after which the trade is rejected.
+    pub deadline: u64,
+}
+
+/// Slippage the client accepts on the trades it sends.
+#[derive(Debug, Clone, Copy)]
+pub struct SlippageTolerance {
+    /// Largest shortfall from the previewed amount, in basis points.
+    pub max_slippage: u64,
+    /// Time a trade stays valid after it is sent, in milliseconds.
+    pub validity: u64,
+}
+
+impl SlippageTolerance {
+    /// Returns the limits of a trade previewed to return `expected_out` and
+    /// sent at `now`.
+    pub fn limits(&self, expected_out: U256, now: u64) -> SlippageLimits {
+        let kept = BASIS_POINTS - self.max_slippage.min(BASIS_POINTS);
+        SlippageLimits {
+            min_out: expected_out * U256::from(kept) / U256::from(BASIS_POINTS),
+            deadline: now + self.validity,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TradingAction {
-    GoLong { amount: U256 },
-    GoShort { amount: U256 },
-    StopLong { amount: U256 },
-    StopShort { amount: U256 },
+    GoLong {
+        amount: U256,
+        limits: Option<SlippageLimits>,
+    },
+    GoShort {
+        amount: U256,
+        limits: Option<SlippageLimits>,
+    },
+    StopLong {
+        amount: U256,
+        limits: Option<SlippageLimits>,
+    },
+    StopShort {
+        amount: U256,
+        limits: Option<SlippageLimits>,
+    },
+    /// Exchanges `amount` tokens of the `from` side for the opposite side.
+    Flip { from: Side, amount: U256 },
 }
 
 impl TradingAction {
-    pub fn to_transfer_order(&self) -> TransferOrder {
+    pub fn amount(&self) -> U256 {
         match self {
-            TradingAction::GoLong { amount } => TransferOrder {
+            TradingAction::GoLong { amount, .. }
+            | TradingAction::GoShort { amount, .. }
+            | TradingAction::StopLong { amount, .. }
+            | TradingAction::StopShort { amount, .. }
+            | TradingAction::Flip { amount, .. } => *amount,
+        }
+    }
+
+    pub fn limits(&self) -> Option<SlippageLimits> {
+        match self {
+            TradingAction::GoLong { limits, .. }
+            | TradingAction::GoShort { limits, .. }
+            | TradingAction::StopLong { limits, .. }
+            | TradingAction::StopShort { limits, .. } => *limits,
+            TradingAction::Flip { .. } => None,
+        }
+    }
+
+    /// Returns the same action protected by the given limits. Flips don't
+    /// support limits and are returned unchanged.
+    pub fn with_limits(mut self, new_limits: SlippageLimits) -> Self {
+        match &mut self {
+            TradingAction::GoLong { limits, .. }
+            | TradingAction::GoShort { limits, .. }
+            | TradingAction::StopLong { limits, .. }
+            | TradingAction::StopShort { limits, .. } => *limits = Some(new_limits),
+            TradingAction::Flip { .. } => {}
+        }
+        self
+    }
+
+    /// Converts the action into a transfer. The transfer interface does not
+    /// support slippage limits, so they are ignored. Flips have no transfer
+    /// equivalent.
+    pub fn to_transfer_order(&self) -> Option<TransferOrder> {
+        let amount = self.amount();
+        let order = match self {
+            TradingAction::GoLong { .. } => TransferOrder {
                 token: Token::Wcspr,
                 recipient: Recipient::LongContract,
-                amount: *amount,
+                amount,
             },
-            TradingAction::GoShort { amount } => TransferOrder {
+            TradingAction::GoShort { .. } => TransferOrder {
                 token: Token::Wcspr,
                 recipient: Recipient::ShortContract,
-                amount: *amount,
+                amount,
             },
-            TradingAction::StopLong { amount } => TransferOrder {
+            TradingAction::StopLong { .. } => TransferOrder {
                 token: Token::Long,
                 recipient: Recipient::WcsprContract,
-                amount: *amount,
+                amount,
             },
-            TradingAction::StopShort { amount } => TransferOrder {
+            TradingAction::StopShort { .. } => TransferOrder {
                 token: Token::Short,
                 recipient: Recipient::WcsprContract,
-                amount: *amount,
+                amount,
             },
-        }
+            TradingAction::Flip { .. } => return None,
+        };
+        Some(order)
     }
 }
 
@@ -0,0 +1,206 @@
+//! Trading competition described in `TRADING_COMPETITION.md`.
+//!
+//! Participants register between the start and the end, and are ranked by
+//! their balance at the end. Balances can't be read back in time, so they
+//! are taken once, by the first `finalize` from the end on. Anyone can call
+//! it, so neither the organizer nor a participant can hold the snapshot back
+//! to pick a better moment: any other party can take it at the end.
+use odra::{casper_types::U256, module::Module, prelude::*};
+use odra_modules::access::Ownable;
+
+use crate::config::{Config, ConfigModule};
+
+/// Most participants a competition can take, so that `finalize` ranks all of
+/// them within the gas limit of a single call.
+pub static MAX_PARTICIPANTS: u32 = 100;
+
+#[odra::module]
+pub struct Competition {
+    admin: SubModule<Ownable>,
+    cfg: SubModule<ConfigModule>,
+    start_time: Var<u64>,
+    end_time: Var<u64>,
+    /// Prize of each place, starting from the first.
+    prizes: Var<Vec<U256>>,
+    prizes_funded: Var<bool>,
+    max_participants: Var<u32>,
+    participants: List<Address>,
+    registered: Mapping<Address, bool>,
+    ranking: Var<Vec<Standing>>,
+}
+
+#[odra::module]
+impl Competition {
+    pub fn init(
+        &mut self,
+        start_time: u64,
+        end_time: u64,
+        prizes: Vec<U256>,
+        max_participants: u32,
+    ) {
+        if start_time >= end_time {
+            self.env().revert(CompetitionError::InvalidSchedule);
+        }
+        if max_participants == 0 || max_participants > MAX_PARTICIPANTS {
+            self.env().revert(CompetitionError::InvalidParticipantLimit);
+        }
+        self.start_time.set(start_time);
+        self.end_time.set(end_time);
+        self.prizes.set(prizes);
+        self.max_participants.set(max_participants);
+        self.admin.init();
+    }
+
+    pub fn set_config(&mut self, cfg: Config) {
+        self.admin.assert_owner(&self.env().caller());
+        self.cfg.set(cfg);
+    }
+
+    /// Moves the total prize pool from the caller into escrow.
+    pub fn fund_prizes(&mut self) {
+        let caller = self.env().caller();
+        self.admin.assert_owner(&caller);
+        if self.prizes_funded.get_or_default() {
+            self.env().revert(CompetitionError::PrizesAlreadyFunded);
+        }
+        let total = self
+            .get_prizes()
+            .iter()
+            .fold(U256::zero(), |acc, prize| acc + prize);
+        self.cfg
+            .wcspr_token()
+            .transfer_from(&caller, &self.env().self_address(), &total);
+        self.prizes_funded.set(true);
+    }
+
+    /// Registers the caller. Registration is open from the start until the
+    /// end, for at most the participant limit.
+    pub fn register(&mut self) {
+        let caller = self.env().caller();
+        let block_time = self.env().get_block_time();
+        if block_time < self.get_start_time() {
+            self.env().revert(CompetitionError::CompetitionNotStarted);
+        }
+        if block_time >= self.get_end_time() {
+            self.env().revert(CompetitionError::CompetitionEnded);
+        }
+        if self.is_registered(&caller) {
+            self.env().revert(CompetitionError::AlreadyRegistered);
+        }
+        if self.participants.len() >= self.get_max_participants() {
+            self.env().revert(CompetitionError::TooManyParticipants);
+        }
+        self.registered.set(&caller, true);
+        self.participants.push(caller);
+    }
+
+    /// Snapshots the balances of the participants, ranks them by it and
+    /// pays the prizes.
+    ///
+    /// The balance is the WCSPR balance plus the redemption value of the
+    /// LONG and SHORT tokens. Anyone can finalize from the end on. Ties keep
+    /// the registration order.
+    pub fn finalize(&mut self) {
+        if self.env().get_block_time() < self.get_end_time() {
+            self.env().revert(CompetitionError::CompetitionNotEnded);
+        }
+        if self.is_finalized() {
+            self.env().revert(CompetitionError::AlreadyFinalized);
+        }
+        if !self.prizes_funded.get_or_default() {
+            self.env().revert(CompetitionError::PrizesNotFunded);
+        }
+
+        let mut ranking: Vec<Standing> = self
+            .participants
+            .iter()
+            .map(|account| Standing {
+                account,
+                final_balance: self.balance_of(&account),
+                prize: U256::zero(),
+            })
+            .collect();
+        ranking.sort_by_key(|standing| core::cmp::Reverse(standing.final_balance));
+
+        let mut unclaimed = U256::zero();
+        let prizes = self.get_prizes();
+        for (place, prize) in prizes.iter().enumerate() {
+            match ranking.get_mut(place) {
+                Some(standing) => {
+                    standing.prize = *prize;
+                    self.cfg.wcspr_token().transfer(&standing.account, prize);
+                }
+                None => unclaimed += *prize,
+            }
+        }
+
+        // Prizes without a winner go back to the organizer.
+        if !unclaimed.is_zero() {
+            let owner = self.admin.get_owner();
+            self.cfg.wcspr_token().transfer(&owner, &unclaimed);
+        }
+        self.ranking.set(ranking);
+    }
+
+    pub fn get_start_time(&self) -> u64 {
+        self.start_time.get().unwrap_or_revert(&self.env())
+    }
+
+    pub fn get_end_time(&self) -> u64 {
+        self.end_time.get().unwrap_or_revert(&self.env())
+    }
+
+    pub fn get_max_participants(&self) -> u32 {
+        self.max_participants.get_or_default()
+    }
+
+    pub fn get_prizes(&self) -> Vec<U256> {
+        self.prizes.get_or_default()
+    }
+
+    pub fn is_registered(&self, account: &Address) -> bool {
+        self.registered.get_or_default(account)
+    }
+
+    pub fn get_participants(&self) -> Vec<Address> {
+        self.participants.iter().collect()
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        self.ranking.get().is_some()
+    }
+
+    /// Returns the final ranking, empty until the competition is finalized.
+    pub fn get_ranking(&self) -> Vec<Standing> {
+        self.ranking.get_or_default()
+    }
+}
+
+impl Competition {
+    fn balance_of(&self, account: &Address) -> U256 {
+        let position = self.cfg.market().get_position(account);
+        self.cfg.wcspr_token().balance_of(account) + position.long_value + position.short_value
+    }
+}
+
+/// Place of a participant in the final ranking.
+#[odra::odra_type]
+pub struct Standing {
+    pub account: Address,
+    pub final_balance: U256,
+    pub prize: U256,
+}
+
+#[odra::odra_error]
+pub enum CompetitionError {
+    InvalidSchedule = 9001,
+    CompetitionEnded = 9002,
+    CompetitionNotEnded = 9003,
+    AlreadyRegistered = 9004,
+    AlreadyFinalized = 9005,
+    PrizesNotFunded = 9006,
+    PrizesAlreadyFunded = 9007,
+    CompetitionNotStarted = 9008,
+    TooManyParticipants = 9009,
+    InvalidParticipantLimit = 9010,
+}
@@ -1,4 +1,4 @@
-use odra::{module::Module, Address, ContractRef, UnwrapOrRevert, Var};
+use odra::{module::Module, prelude::*, ContractRef};
 use odra_modules::cep18_token::Cep18ContractRef;
 
 use crate::{market::MarketContractRef, token_long::TokenLongContractRef};
@@ -52,6 +52,11 @@ impl ConfigModule {
         self.state.get().unwrap_or_revert(&self.env())
     }
 
+    /// Returns the config, or `None` if it was never set.
+    pub fn try_get(&self) -> Option<Config> {
+        self.state.get()
+    }
+
     pub fn long_token(&self) -> TokenLongContractRef {
         let addr = self.get().long_token;
         TokenLongContractRef::new(self.env(), addr)
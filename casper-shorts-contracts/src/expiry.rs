@@ -0,0 +1,93 @@
+//! Expiry of dated markets.
+//!
+//! A dated market stops at its expiry. The last price set before it is the
+//! final price, deposits and price updates are refused, and holders redeem
+//! their tokens at the settled liquidity without the withdrawal fee.
+//! Markets without an expiry are perpetual.
+//!
+//! The owner can settle any market early, which expires it at once. The
+//! WCSPR left in the market a grace period after the expiry can be swept.
+use odra::{casper_types::U256, module::Module, prelude::*};
+
+/// A week, in milliseconds of the block time.
+pub static WEEK: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Time holders have to redeem after the expiry before the rest of the
+/// liquidity can be swept. 180 days, in milliseconds of the block time.
+pub static SWEEP_GRACE_PERIOD: u64 = 180 * 24 * 60 * 60 * 1000;
+
+#[odra::event]
+pub struct MarketSettled {
+    pub price: U256,
+    pub settled_at: u64,
+}
+
+#[odra::event]
+pub struct LiquiditySwept {
+    pub recipient: Address,
+    pub amount: U256,
+}
+
+#[odra::module(events = [MarketSettled, LiquiditySwept])]
+pub struct MarketExpiry {
+    expiry: Var<u64>,
+}
+
+impl MarketExpiry {
+    /// Sets the expiry of a dated market. It must be in the future.
+    pub fn init(&mut self, expiry: Option<u64>) {
+        let Some(expiry) = expiry else {
+            return;
+        };
+        if expiry <= self.env().get_block_time() {
+            self.env().revert(ExpiryError::InvalidExpiry);
+        }
+        self.expiry.set(expiry);
+    }
+
+    pub fn get(&self) -> Option<u64> {
+        self.expiry.get()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.get()
+            .is_some_and(|expiry| self.env().get_block_time() >= expiry)
+    }
+
+    pub fn assert_not_expired(&self) {
+        if self.is_expired() {
+            self.env().revert(ExpiryError::MarketExpired);
+        }
+    }
+
+    /// Expires the market now at the final `price`.
+    pub fn settle(&mut self, price: U256) {
+        self.assert_not_expired();
+        let settled_at = self.env().get_block_time();
+        self.expiry.set(settled_at);
+        self.env().emit_event(MarketSettled { price, settled_at });
+    }
+
+    /// Reverts unless the grace period after the expiry is over.
+    pub fn assert_sweepable(&self) {
+        let Some(expiry) = self.get().filter(|_| self.is_expired()) else {
+            self.env().revert(ExpiryError::MarketNotExpired);
+        };
+        if self.env().get_block_time() < expiry + SWEEP_GRACE_PERIOD {
+            self.env().revert(ExpiryError::GracePeriodNotOver);
+        }
+    }
+
+    pub fn on_swept(&self, recipient: Address, amount: U256) {
+        self.env()
+            .emit_event(LiquiditySwept { recipient, amount });
+    }
+}
+
+#[odra::odra_error]
+pub enum ExpiryError {
+    MarketExpired = 14001,
+    InvalidExpiry = 14002,
+    MarketNotExpired = 14003,
+    GracePeriodNotOver = 14004,
+}
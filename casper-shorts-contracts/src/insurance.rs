@@ -0,0 +1,76 @@
+//! Insurance fund covering losses the pool can't pay by itself.
+use odra::{casper_types::U256, prelude::*};
+
+use crate::system::BASIS_POINTS;
+
+/// Reason of an insurance payout.
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum PayoutReason {
+    /// A price move the losing side could not fully pay.
+    CappedMove,
+    /// WCSPR held by the market fell below the recorded liquidity.
+    Deficit,
+}
+
+#[odra::odra_type]
+pub struct Payout {
+    pub reason: PayoutReason,
+    pub amount: U256,
+    pub block_time: u64,
+}
+
+#[odra::module]
+pub struct InsuranceFund {
+    fee_share: Var<u64>,
+    balance: Var<U256>,
+    payouts: List<Payout>,
+}
+
+impl InsuranceFund {
+    /// Share of the fees added to the fund, in basis points.
+    pub fn fee_share(&self) -> u64 {
+        self.fee_share.get_or_default()
+    }
+
+    pub fn set_fee_share(&mut self, fee_share: u64) {
+        self.fee_share.set(fee_share);
+    }
+
+    /// Adds the fund's share of `fee` to the fund and returns the rest.
+    pub fn take_fee_share(&mut self, fee: U256) -> U256 {
+        let share = fee * U256::from(self.fee_share()) / U256::from(BASIS_POINTS);
+        self.balance.set(self.balance() + share);
+        fee - share
+    }
+
+    pub fn balance(&self) -> U256 {
+        self.balance.get_or_default()
+    }
+
+    /// Pays up to `amount` from the fund and returns the amount paid.
+    pub fn pay(&mut self, reason: PayoutReason, amount: U256, block_time: u64) -> U256 {
+        let paid = amount.min(self.balance());
+        if paid.is_zero() {
+            return paid;
+        }
+        self.balance.set(self.balance() - paid);
+        self.payouts.push(Payout {
+            reason,
+            amount: paid,
+            block_time,
+        });
+        paid
+    }
+
+    /// Empties the fund and returns its balance.
+    pub fn sweep(&mut self) -> U256 {
+        let balance = self.balance();
+        self.balance.set(U256::zero());
+        balance
+    }
+
+    pub fn payouts(&self) -> Vec<Payout> {
+        self.payouts.iter().collect()
+    }
+}
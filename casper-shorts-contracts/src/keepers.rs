@@ -0,0 +1,110 @@
+//! Rewards for keepers updating the price and executing orders.
+use odra::{casper_types::U256, prelude::*};
+
+use crate::system::BASIS_POINTS;
+
+/// Keeper reward parameters. The default config pays no rewards.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct KeeperConfig {
+    /// Share of the trading fees added to the reward pool, in basis points.
+    pub fee_share: u64,
+    /// Reward for submitting a fresh price.
+    pub price_update_reward: U256,
+    /// Reward for each executed order. The order's owner pays it into the
+    /// pool from the withdrawn collateral, so executing one's own orders
+    /// earns nothing.
+    pub order_execution_reward: U256,
+    /// Length of a reward round, in the block time units.
+    pub round_duration: u64,
+    /// Total rewards paid to all keepers in a single round.
+    pub max_rewards_per_round: U256,
+}
+
+impl KeeperConfig {
+    pub fn is_valid(&self) -> bool {
+        self.fee_share <= BASIS_POINTS
+    }
+}
+
+#[odra::module]
+pub struct KeeperRewards {
+    config: Var<KeeperConfig>,
+    keepers: Mapping<Address, bool>,
+    pool: Var<U256>,
+    round: Var<u64>,
+    paid_in_round: Var<U256>,
+    earnings: Mapping<Address, U256>,
+}
+
+impl KeeperRewards {
+    pub fn config(&self) -> KeeperConfig {
+        self.config.get_or_default()
+    }
+
+    pub fn set_config(&mut self, config: KeeperConfig) {
+        self.config.set(config);
+    }
+
+    pub fn is_keeper(&self, account: &Address) -> bool {
+        self.keepers.get_or_default(account)
+    }
+
+    pub fn set_keeper(&mut self, account: &Address, is_keeper: bool) {
+        self.keepers.set(account, is_keeper);
+    }
+
+    /// Adds the keepers' share of `fee` to the pool and returns the rest.
+    pub fn take_fee_share(&mut self, fee: U256) -> U256 {
+        let share = fee * U256::from(self.config().fee_share) / U256::from(BASIS_POINTS);
+        self.pool.set(self.pool() + share);
+        fee - share
+    }
+
+    /// Adds the execution fee of an order to the pool.
+    pub fn add_to_pool(&mut self, amount: U256) {
+        self.pool.set(self.pool() + amount);
+    }
+
+    pub fn pool(&self) -> U256 {
+        self.pool.get_or_default()
+    }
+
+    /// Empties the pool and returns what it held.
+    pub fn sweep_pool(&mut self) -> U256 {
+        let pool = self.pool();
+        self.pool.set(U256::zero());
+        pool
+    }
+
+    pub fn earnings(&self, keeper: &Address) -> U256 {
+        self.earnings.get_or_default(keeper)
+    }
+
+    /// Books a reward of `amount` for `keeper` at `block_time` and returns
+    /// the amount to pay, limited by the pool and the round cap.
+    pub fn reward(&mut self, keeper: &Address, amount: U256, block_time: u64) -> U256 {
+        let config = self.config();
+        let round = match config.round_duration {
+            0 => block_time,
+            duration => block_time / duration,
+        };
+        let paid_in_round = if self.round.get_or_default() == round {
+            self.paid_in_round.get_or_default()
+        } else {
+            U256::zero()
+        };
+
+        let left_in_round = config.max_rewards_per_round.saturating_sub(paid_in_round);
+        let reward = amount.min(left_in_round).min(self.pool());
+        if reward.is_zero() {
+            return reward;
+        }
+
+        self.round.set(round);
+        self.paid_in_round.set(paid_in_round + reward);
+        self.pool.set(self.pool() - reward);
+        self.earnings.set(keeper, self.earnings(keeper) + reward);
+        reward
+    }
+}
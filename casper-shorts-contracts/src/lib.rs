@@ -1,11 +1,25 @@
 #![cfg_attr(not(test), no_std)]
 #![cfg_attr(not(test), no_main)]
+#![recursion_limit = "512"]
 extern crate alloc;
 
+pub mod competition;
 pub mod config;
+pub mod expiry;
+pub mod insurance;
+pub mod keepers;
+pub mod limits;
 pub mod market;
+pub mod multisig;
+pub mod orders;
+pub mod pnl;
 pub mod price_data;
+pub mod registry;
+pub mod routes;
+pub mod settlement;
 pub mod system;
+pub mod timelock;
 pub mod token_long;
 pub mod token_short;
 pub mod token_wcspr;
+pub mod version;
@@ -0,0 +1,286 @@
+//! Bounds on the pool size, single positions, trade sizes and holding time.
+use odra::{casper_types::U256, prelude::*};
+
+use crate::system::{MarketState, Side, BASIS_POINTS};
+
+/// Trading limits of the market. Zero disables a limit.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct MarketLimits {
+    /// Maximum WCSPR liquidity of each side.
+    pub max_side_liquidity: U256,
+    /// Maximum share of a side held by a single account, in basis points.
+    pub max_account_share: u64,
+    /// Side liquidity from which the account share limit applies, so the
+    /// first deposits can bootstrap the side.
+    pub account_share_threshold: U256,
+    /// Minimum WCSPR amount of a deposit.
+    pub min_deposit: U256,
+    /// Minimum token amount of a withdrawal, unless the whole balance is
+    /// withdrawn.
+    pub min_withdraw: U256,
+}
+
+impl MarketLimits {
+    /// Liquidity the side can accept before reaching the cap, if capped.
+    pub fn side_capacity(&self, state: &MarketState, side: Side) -> Option<U256> {
+        if self.max_side_liquidity.is_zero() {
+            return None;
+        }
+        Some(
+            self.max_side_liquidity
+                .saturating_sub(state.liquidity(side)),
+        )
+    }
+
+    pub fn is_deposit_too_small(&self, amount: U256) -> bool {
+        amount < self.min_deposit
+    }
+
+    pub fn is_withdraw_too_small(&self, tokens: U256, balance: U256) -> bool {
+        tokens < self.min_withdraw && tokens != balance
+    }
+
+    pub fn exceeds_side_liquidity(&self, state: &MarketState, side: Side) -> bool {
+        !self.max_side_liquidity.is_zero() && state.liquidity(side) > self.max_side_liquidity
+    }
+
+    /// Checks if `balance` tokens are too big a share of the side.
+    pub fn exceeds_account_share(&self, state: &MarketState, side: Side, balance: U256) -> bool {
+        if self.max_account_share == 0 || state.liquidity(side) < self.account_share_threshold {
+            return false;
+        }
+        let max_share = U256::from(self.max_account_share.min(BASIS_POINTS));
+        state.pool_share(side, balance) > max_share
+    }
+}
+
+/// What happens to a deposit exceeding the imbalance guard.
+#[odra::odra_type]
+#[derive(Copy, Default)]
+pub enum ImbalanceMode {
+    /// The deposit reverts.
+    #[default]
+    Revert,
+    /// The deposit is filled up to the capacity and the rest stays with the
+    /// depositor.
+    PartialFill,
+}
+
+/// Keeps the liquidity of a side within a ratio of the other side, so both
+/// sides keep tracking the price.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct ImbalanceGuard {
+    /// Maximum liquidity of a side relative to the other side, in basis
+    /// points. Zero disables the guard.
+    pub max_ratio: u64,
+    pub mode: ImbalanceMode,
+}
+
+impl ImbalanceGuard {
+    pub fn is_valid(&self) -> bool {
+        self.max_ratio == 0 || self.max_ratio >= BASIS_POINTS
+    }
+
+    /// Maximum liquidity of the side. The guard is skipped while the other
+    /// side is empty.
+    pub fn max_liquidity(&self, state: &MarketState, side: Side) -> Option<U256> {
+        let opposite = state.liquidity(side.opposite());
+        if self.max_ratio == 0 || opposite.is_zero() {
+            return None;
+        }
+        Some(opposite * U256::from(self.max_ratio) / U256::from(BASIS_POINTS))
+    }
+
+    /// Liquidity the side can accept before exceeding the ratio, if guarded.
+    pub fn capacity(&self, state: &MarketState, side: Side) -> Option<U256> {
+        self.max_liquidity(state, side)
+            .map(|max| max.saturating_sub(state.liquidity(side)))
+    }
+
+    pub fn is_exceeded(&self, state: &MarketState, side: Side) -> bool {
+        self.max_liquidity(state, side)
+            .is_some_and(|max| state.liquidity(side) > max)
+    }
+}
+
+/// Extra fee for withdrawals made shortly after a deposit, paid to the
+/// opposite side, or to the side left on a flip. A zero period disables it.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct EarlyExitFee {
+    /// Time after the last deposit during which the fee applies.
+    pub holding_period: u64,
+    /// Fee in basis points of the withdrawn collateral.
+    pub fee: u64,
+}
+
+impl EarlyExitFee {
+    pub fn is_valid(&self) -> bool {
+        self.fee <= BASIS_POINTS
+    }
+
+    /// Time from which a position opened at `last_deposit` exits freely.
+    pub fn unlock_time(&self, last_deposit: u64) -> u64 {
+        last_deposit.saturating_add(self.holding_period)
+    }
+
+    /// Fee for withdrawing `collateral` at `now` from a position with the
+    /// last deposit at `last_deposit`.
+    pub fn fee_of(&self, collateral: U256, last_deposit: u64, now: u64) -> U256 {
+        if self.holding_period == 0 || now >= self.unlock_time(last_deposit) {
+            return U256::zero();
+        }
+        collateral * U256::from(self.fee) / U256::from(BASIS_POINTS)
+    }
+}
+
+/// Limits, fees and the emergency pause set by the market owner.
+#[odra::module]
+pub struct TradingRules {
+    flip_fee: Var<u64>,
+    limits: Var<MarketLimits>,
+    imbalance_guard: Var<ImbalanceGuard>,
+    early_exit_fee: Var<EarlyExitFee>,
+    paused: Var<bool>,
+}
+
+impl TradingRules {
+    /// Flip fee in basis points, if set.
+    pub fn flip_fee(&self) -> Option<u64> {
+        self.flip_fee.get()
+    }
+
+    pub fn set_flip_fee(&mut self, fee: u64) {
+        self.flip_fee.set(fee);
+    }
+
+    pub fn limits(&self) -> MarketLimits {
+        self.limits.get_or_default()
+    }
+
+    pub fn set_limits(&mut self, limits: MarketLimits) {
+        self.limits.set(limits);
+    }
+
+    pub fn imbalance_guard(&self) -> ImbalanceGuard {
+        self.imbalance_guard.get_or_default()
+    }
+
+    pub fn set_imbalance_guard(&mut self, guard: ImbalanceGuard) {
+        self.imbalance_guard.set(guard);
+    }
+
+    pub fn early_exit_fee(&self) -> EarlyExitFee {
+        self.early_exit_fee.get_or_default()
+    }
+
+    pub fn set_early_exit_fee(&mut self, early_exit_fee: EarlyExitFee) {
+        self.early_exit_fee.set(early_exit_fee);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.get_or_default()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused.set(paused);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> MarketState {
+        MarketState {
+            long_total_supply: U256::from(100),
+            short_total_supply: U256::from(50),
+            long_liquidity: U256::from(200),
+            short_liquidity: U256::from(50),
+            price: U256::from(100),
+        }
+    }
+
+    #[test]
+    fn default_limits_allow_everything() {
+        let limits = MarketLimits::default();
+        let state = state();
+        assert!(!limits.is_deposit_too_small(U256::one()));
+        assert!(!limits.is_withdraw_too_small(U256::one(), U256::from(10)));
+        assert!(!limits.exceeds_side_liquidity(&state, Side::Long));
+        assert!(!limits.exceeds_account_share(&state, Side::Long, U256::from(100)));
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let limits = MarketLimits {
+            max_side_liquidity: U256::from(100),
+            max_account_share: 2_000,
+            account_share_threshold: U256::from(100),
+            min_deposit: U256::from(10),
+            min_withdraw: U256::from(5),
+        };
+        let state = state();
+        assert!(limits.is_deposit_too_small(U256::from(9)));
+        assert!(!limits.is_deposit_too_small(U256::from(10)));
+
+        // Dust can be withdrawn only as the whole balance.
+        assert!(limits.is_withdraw_too_small(U256::from(4), U256::from(10)));
+        assert!(!limits.is_withdraw_too_small(U256::from(4), U256::from(4)));
+
+        assert!(limits.exceeds_side_liquidity(&state, Side::Long));
+        assert!(!limits.exceeds_side_liquidity(&state, Side::Short));
+
+        // 21 of 100 LONG tokens is above 20%.
+        assert!(limits.exceeds_account_share(&state, Side::Long, U256::from(21)));
+        assert!(!limits.exceeds_account_share(&state, Side::Long, U256::from(20)));
+
+        // The short side is below the threshold.
+        assert!(!limits.exceeds_account_share(&state, Side::Short, U256::from(50)));
+    }
+
+    #[test]
+    fn imbalance_guard() {
+        let guard = ImbalanceGuard {
+            max_ratio: 30_000,
+            mode: ImbalanceMode::Revert,
+        };
+        let state = state();
+        // Long can grow up to 3 times the short liquidity.
+        assert_eq!(guard.capacity(&state, Side::Long), Some(U256::zero()));
+        assert_eq!(guard.capacity(&state, Side::Short), Some(U256::from(550)));
+        assert!(guard.is_exceeded(&state, Side::Long));
+        assert!(!guard.is_exceeded(&state, Side::Short));
+
+        let empty_short = MarketState {
+            short_liquidity: U256::zero(),
+            ..state
+        };
+        assert_eq!(guard.capacity(&empty_short, Side::Long), None);
+        assert!(!ImbalanceGuard::default().is_exceeded(&state, Side::Long));
+        assert!(!ImbalanceGuard {
+            max_ratio: 5_000,
+            mode: ImbalanceMode::Revert
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn early_exit_fee() {
+        let fee = EarlyExitFee {
+            holding_period: 100,
+            fee: 500,
+        };
+        let collateral = U256::from(1_000);
+        assert_eq!(fee.fee_of(collateral, 50, 149), U256::from(50));
+        assert_eq!(fee.fee_of(collateral, 50, 150), U256::zero());
+        assert_eq!(EarlyExitFee::default().fee_of(collateral, 50, 50), U256::zero());
+        assert!(!EarlyExitFee {
+            holding_period: 100,
+            fee: 10_001
+        }
+        .is_valid());
+    }
+}
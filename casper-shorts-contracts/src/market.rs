@@ -1,10 +1,21 @@
-use odra::{
-    casper_types::U256, module::Module, prelude::*, Address, SubModule, UnwrapOrRevert, Var,
-};
+use odra::{casper_types::U256, module::Module, prelude::*};
 use odra_modules::access::Ownable;
 
 use crate::{
-    config::{Config, ConfigModule}, price_data::PriceData, system::{MarketState, Side}
+    config::{Config, ConfigModule},
+    expiry::MarketExpiry,
+    insurance::{InsuranceFund, Payout, PayoutReason},
+    keepers::{KeeperConfig, KeeperRewards},
+    limits::{EarlyExitFee, ImbalanceGuard, ImbalanceMode, MarketLimits, TradingRules},
+    orders::{Order, OrderBook, OrderStatus, Trigger, MIN_ORDER_VALUE},
+    pnl::{Pnl, PnlReport, PnlTracker, SidePnl},
+    price_data::PriceData,
+    settlement::{
+        Claimable, PendingRequest, RequestKind, SettlementQueue, MAX_QUEUED_REQUESTS,
+    },
+    system::{MarketState, Payoff, Side, BASIS_POINTS},
+    timelock::{ConfigTimelock, PendingConfig, TimelockError},
+    version::StateVersion,
 };
 
 #[odra::module]
@@ -12,78 +23,866 @@ pub struct Market {
     admin: SubModule<Ownable>,
     cfg: SubModule<ConfigModule>,
     state: Var<MarketState>,
+    rewards_destination: Var<RewardsDestination>,
+    pnl: SubModule<PnlTracker>,
+    orders: SubModule<OrderBook>,
+    keepers: SubModule<KeeperRewards>,
+    insurance: SubModule<InsuranceFund>,
+    settlement: SubModule<SettlementQueue>,
+    last_price: Var<PriceData>,
+    rules: SubModule<TradingRules>,
+    timelock: SubModule<ConfigTimelock>,
+    version: SubModule<StateVersion>,
+    expiry: SubModule<MarketExpiry>,
+    /// Appended, as fields are stored by position.
+    payoff: Var<Payoff>,
 }
 
 #[odra::module]
 impl Market {
-    pub fn init(&mut self, last_price: PriceData) {
+    /// Creates a perpetual market, or a dated one if `expiry` is given.
+    /// Price changes move the liquidity following `payoff`.
+    pub fn init(&mut self, last_price: PriceData, expiry: Option<u64>, payoff: Payoff) {
+        if !payoff.is_valid() {
+            self.env().revert(MarketError::InvalidPayoff);
+        }
+        self.payoff.set(payoff);
         self.state.set(MarketState::new(last_price.price));
+        self.last_price.set(last_price);
         self.admin.init();
+        self.version.init();
+        self.expiry.init(expiry);
     }
 
+    #[odra(non_reentrant)]
     pub fn deposit_long(&mut self, amount: U256) {
         self.deposit_unchecked(&self.env().caller(), Side::Long, amount);
     }
 
+    /// Deposit hook of the WCSPR token, called when `sender` transfers
+    /// WCSPR to the LONG token. Only the WCSPR token can call it, as the
+    /// market pulls the WCSPR of `sender` without an allowance.
+    #[odra(non_reentrant)]
     pub fn deposit_long_from(&mut self, sender: &Address, amount: U256) {
-        if self.cfg.get().is_long_token(&self.env().caller()) {
+        if !self.cfg.get().is_wcspr_token(&self.env().caller()) {
             self.env()
-                .revert(MarketError::LongTokenContractNotACallerOnDeposit);
+                .revert(MarketError::WCSPRTokenContractNotACallerOnLongDeposit);
         }
         self.deposit_unchecked(sender, Side::Long, amount);
     }
 
+    #[odra(non_reentrant)]
     pub fn deposit_short(&mut self, amount: U256) {
         self.deposit_unchecked(&self.env().caller(), Side::Short, amount);
     }
 
+    /// Deposit hook of the WCSPR token, called when `sender` transfers
+    /// WCSPR to the SHORT token. Only the WCSPR token can call it.
+    #[odra(non_reentrant)]
     pub fn deposit_short_from(&mut self, sender: &Address, amount: U256) {
-        if self.cfg.get().is_short_token(&self.env().caller()) {
+        if !self.cfg.get().is_wcspr_token(&self.env().caller()) {
             self.env()
-                .revert(MarketError::ShortTokenContractNotACallerOnDeposit);
+                .revert(MarketError::WCSPRTokenContractNotACallerOnShortDeposit);
         }
         self.deposit_unchecked(sender, Side::Short, amount);
     }
 
+    /// Deposits `amount` WCSPR of the caller into the long side and mints
+    /// the LONG tokens to `recipient`.
+    #[odra(non_reentrant)]
+    pub fn deposit_long_for(&mut self, recipient: &Address, amount: U256) {
+        self.assert_valid_recipient(recipient);
+        self.deposit_for_unchecked(&self.env().caller(), recipient, Side::Long, amount);
+    }
+
+    /// Deposits `amount` WCSPR of the caller into the short side and mints
+    /// the SHORT tokens to `recipient`.
+    #[odra(non_reentrant)]
+    pub fn deposit_short_for(&mut self, recipient: &Address, amount: U256) {
+        self.assert_valid_recipient(recipient);
+        self.deposit_for_unchecked(&self.env().caller(), recipient, Side::Short, amount);
+    }
+
+    #[odra(non_reentrant)]
     pub fn withdraw_long(&mut self, amount: U256) {
         self.withdrawal_unchecked(&self.env().caller(), Side::Long, amount);
     }
 
+    /// Withdrawal hook of the LONG token, called when `sender` transfers
+    /// LONG tokens to the WCSPR token. Only the LONG token can call it.
+    #[odra(non_reentrant)]
     pub fn withdraw_long_from(&mut self, sender: &Address, amount: U256) {
-        if self.cfg.get().is_wcspr_token(&self.env().caller()) {
+        if !self.cfg.get().is_long_token(&self.env().caller()) {
             self.env()
                 .revert(MarketError::LongTokenContractNotACallerOnWithdrawal);
         }
         self.withdrawal_unchecked(sender, Side::Long, amount);
     }
 
+    #[odra(non_reentrant)]
     pub fn withdraw_short(&mut self, amount: U256) {
         self.withdrawal_unchecked(&self.env().caller(), Side::Short, amount);
     }
 
+    /// Withdrawal hook of the SHORT token, called when `sender` transfers
+    /// SHORT tokens to the WCSPR token. Only the SHORT token can call it.
+    #[odra(non_reentrant)]
     pub fn withdraw_short_from(&mut self, sender: &Address, amount: U256) {
-        if self.cfg.get().is_wcspr_token(&self.env().caller()) {
+        if !self.cfg.get().is_short_token(&self.env().caller()) {
             self.env()
                 .revert(MarketError::ShortTokenContractNotACallerOnWithdrawal);
         }
         self.withdrawal_unchecked(sender, Side::Short, amount);
     }
 
+    /// Burns `amount` LONG tokens of the caller and pays the WCSPR to
+    /// `recipient`.
+    #[odra(non_reentrant)]
+    pub fn withdraw_long_to(&mut self, recipient: &Address, amount: U256) {
+        self.assert_valid_recipient(recipient);
+        let caller = self.env().caller();
+        self.assert_withdraw_size(&caller, Side::Long, amount);
+        self.withdraw_or_queue(&caller, recipient, Side::Long, amount);
+    }
+
+    /// Burns `amount` SHORT tokens of the caller and pays the WCSPR to
+    /// `recipient`.
+    #[odra(non_reentrant)]
+    pub fn withdraw_short_to(&mut self, recipient: &Address, amount: U256) {
+        self.assert_valid_recipient(recipient);
+        let caller = self.env().caller();
+        self.assert_withdraw_size(&caller, Side::Short, amount);
+        self.withdraw_or_queue(&caller, recipient, Side::Short, amount);
+    }
+
+    /// Deposits into the long side, reverting if fewer than `min_tokens_out`
+    /// LONG tokens are minted or the `deadline` has passed.
+    #[odra(non_reentrant)]
+    pub fn deposit_long_with_slippage(
+        &mut self,
+        amount: U256,
+        min_tokens_out: U256,
+        deadline: u64,
+    ) {
+        self.assert_immediate_settlement();
+        self.assert_deadline(deadline);
+        let tokens = self.deposit_unchecked(&self.env().caller(), Side::Long, amount);
+        self.assert_min_tokens_out(tokens, min_tokens_out);
+    }
+
+    /// Deposits into the short side, reverting if fewer than `min_tokens_out`
+    /// SHORT tokens are minted or the `deadline` has passed.
+    #[odra(non_reentrant)]
+    pub fn deposit_short_with_slippage(
+        &mut self,
+        amount: U256,
+        min_tokens_out: U256,
+        deadline: u64,
+    ) {
+        self.assert_immediate_settlement();
+        self.assert_deadline(deadline);
+        let tokens = self.deposit_unchecked(&self.env().caller(), Side::Short, amount);
+        self.assert_min_tokens_out(tokens, min_tokens_out);
+    }
+
+    /// Withdraws from the long side, reverting if less than
+    /// `min_collateral_out` WCSPR is paid out or the `deadline` has passed.
+    #[odra(non_reentrant)]
+    pub fn withdraw_long_with_slippage(
+        &mut self,
+        amount: U256,
+        min_collateral_out: U256,
+        deadline: u64,
+    ) {
+        self.assert_immediate_settlement();
+        self.assert_deadline(deadline);
+        let collateral = self.withdrawal_unchecked(&self.env().caller(), Side::Long, amount);
+        self.assert_min_collateral_out(collateral, min_collateral_out);
+    }
+
+    /// Withdraws from the short side, reverting if less than
+    /// `min_collateral_out` WCSPR is paid out or the `deadline` has passed.
+    #[odra(non_reentrant)]
+    pub fn withdraw_short_with_slippage(
+        &mut self,
+        amount: U256,
+        min_collateral_out: U256,
+        deadline: u64,
+    ) {
+        self.assert_immediate_settlement();
+        self.assert_deadline(deadline);
+        let collateral = self.withdrawal_unchecked(&self.env().caller(), Side::Short, amount);
+        self.assert_min_collateral_out(collateral, min_collateral_out);
+    }
+
+    /// Exchanges `tokens` of `side_from` for tokens of the opposite side in a
+    /// single state transition, paying the flip fee once. Returns the tokens
+    /// minted.
+    #[odra(non_reentrant)]
+    pub fn flip(&mut self, side_from: Side, tokens: U256) -> U256 {
+        self.assert_not_paused();
+        self.expiry.assert_not_expired();
+        self.assert_immediate_settlement();
+        let caller = self.env().caller();
+        let side_to = side_from.opposite();
+        let balance_to = self.balance_of(&caller, side_to);
+        self.assert_withdraw_size(&caller, side_from, tokens);
+
+        let mut state = self.get_state();
+        let collateral = state.on_withdraw(side_from, tokens);
+        let (collateral, fee, _) = self.charge_flip_fees(&mut state, &caller, side_from, collateral);
+        self.assert_not_wiped_out(&state, side_to);
+        let new_tokens = state.on_deposit(side_to, collateral);
+        self.assert_deposit_limits(&state, side_to, balance_to + new_tokens);
+        self.set_state(state);
+        self.collect_fee(&fee);
+
+        self.pnl.on_withdraw(&caller, side_from, tokens, collateral);
+        self.pnl
+            .on_deposit(&caller, side_to, balance_to, new_tokens, collateral);
+
+        match side_from {
+            Side::Long => {
+                self.cfg.long_token().burn(&caller, &tokens);
+                self.cfg.short_token().mint(&caller, &new_tokens);
+            }
+            Side::Short => {
+                self.cfg.short_token().burn(&caller, &tokens);
+                self.cfg.long_token().mint(&caller, &new_tokens);
+            }
+        };
+        new_tokens
+    }
+
+    /// Returns the tokens minted and the fees paid for flipping `tokens` of
+    /// `side_from` held by `account` at the current state, including the
+    /// early-exit fee of `account`.
+    pub fn preview_flip(&self, account: &Address, side_from: Side, tokens: U256) -> TradePreview {
+        let mut state = self.get_state();
+        let collateral = state.on_withdraw(side_from, tokens);
+        let (collateral, fee, early_exit_fee) =
+            self.charge_flip_fees(&mut state, account, side_from, collateral);
+        self.assert_not_wiped_out(&state, side_from.opposite());
+        TradePreview {
+            amount_out: state.on_deposit(side_from.opposite(), collateral),
+            fee,
+            early_exit_fee,
+        }
+    }
+
+    /// Sets the flip fee in basis points. It can't exceed the trading fee.
+    #[odra(non_reentrant)]
+    pub fn set_flip_fee(&mut self, fee: u64) {
+        self.admin.assert_owner(&self.env().caller());
+        if fee > TRADING_FEE {
+            self.env().revert(MarketError::FlipFeeTooHigh);
+        }
+        self.rules.set_flip_fee(fee);
+    }
+
+    /// Returns the flip fee in basis points. Defaults to the trading fee.
+    pub fn get_flip_fee(&self) -> u64 {
+        self.rules.flip_fee().unwrap_or(TRADING_FEE)
+    }
+
+    /// Updates the price. Can be called by the owner or a keeper.
+    ///
+    /// Keepers are rewarded for fresh prices and for the orders executed
+    /// by the update. If the losing side can't pay the full move, the
+    /// insurance fund tops up the winning side. Queued trades are settled
+    /// at the new price. Dated markets refuse new prices after the expiry.
+    #[odra(non_reentrant)]
     pub fn set_price(&mut self, price_data: PriceData) {
+        self.expiry.assert_not_expired();
+        let caller = self.env().caller();
+        let is_keeper = self.keepers.is_keeper(&caller);
+        if !is_keeper {
+            self.admin.assert_owner(&caller);
+        }
+        let price = price_data.price;
+        let is_fresh = self.handle_and_validate_new_price(price_data);
+        self.apply_price(price);
+        self.settlement.next_round();
+        self.settle_ready_requests(MAX_QUEUED_REQUESTS);
+        let executed = self.execute_triggered_orders(MAX_ORDERS_PER_PRICE_UPDATE);
+
+        if is_keeper {
+            let config = self.keepers.config();
+            let mut reward = config.order_execution_reward * executed;
+            if is_fresh {
+                reward += config.price_update_reward;
+            }
+            self.reward_keeper(&caller, reward);
+        }
+    }
+
+    pub fn get_last_price(&self) -> PriceData {
+        self.last_price
+            .get_or_revert_with(MarketError::LastPriceNotSet)
+    }
+
+    pub fn get_payoff(&self) -> Payoff {
+        self.payoff.get_or_default()
+    }
+
+    /// Returns the expiry of a dated market, `None` for a perpetual one.
+    pub fn get_expiry(&self) -> Option<u64> {
+        self.expiry.get()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expiry.is_expired()
+    }
+
+    /// Winds the market down at `price_data`. The price becomes final and
+    /// the market expires now, so LONG and SHORT become fixed claims on
+    /// the liquidity, redeemed without fees and without new prices. Queued
+    /// trades settle with `settle_requests`. Only the owner can call it.
+    #[odra(non_reentrant)]
+    pub fn settle(&mut self, price_data: PriceData) {
+        self.admin.assert_owner(&self.env().caller());
+        self.expiry.assert_not_expired();
+        let price = price_data.price;
+        self.handle_and_validate_new_price(price_data);
+        self.apply_price(price);
+        self.expiry.settle(price);
+    }
+
+    /// Sends all the WCSPR left a grace period after the expiry to
+    /// `recipient`: the unredeemed liquidity, the insurance fund, the keeper
+    /// pool and the unclaimed collateral. Queued trades must be settled
+    /// first with `settle_requests`. Tokens redeemed and collateral claimed
+    /// later pay nothing. Returns the WCSPR swept.
+    #[odra(non_reentrant)]
+    pub fn sweep(&mut self, recipient: &Address) -> U256 {
         self.admin.assert_owner(&self.env().caller());
+        self.expiry.assert_sweepable();
+        if !self.settlement.pending().is_empty() {
+            self.env().revert(MarketError::RequestsPending);
+        }
         let mut state = self.get_state();
-        state.on_price_change(price_data.price);
+        state.long_liquidity = U256::zero();
+        state.short_liquidity = U256::zero();
         self.set_state(state);
+        self.insurance.sweep();
+        self.keepers.sweep_pool();
+        self.settlement.sweep_collateral();
+
+        // Sweep the balance rather than the recorded amounts, so WCSPR sent
+        // to the market directly leaves it too.
+        let amount = self
+            .cfg
+            .wcspr_token()
+            .balance_of(&self.env().self_address());
+        self.withdraw_deposit(recipient, &amount);
+        self.expiry.on_swept(*recipient, amount);
+        amount
+    }
+
+    /// Returns the price the market settled at, once expired.
+    pub fn get_final_price(&self) -> Option<PriceData> {
+        if !self.is_expired() {
+            return None;
+        }
+        Some(self.get_last_price())
+    }
+
+    /// Closes `tokens` of the given side once the price hits the trigger.
+    ///
+    /// The tokens are held in escrow by the market until the order is
+    /// executed or cancelled. They must be worth at least `MIN_ORDER_VALUE`,
+    /// and the number of open orders is capped. Returns the order id.
+    #[odra(non_reentrant)]
+    pub fn place_order(
+        &mut self,
+        side: Side,
+        tokens: U256,
+        trigger: Trigger,
+        trigger_price: U256,
+    ) -> u32 {
+        self.expiry.assert_not_expired();
+        if tokens.is_zero() {
+            self.env().revert(MarketError::InvalidOrderAmount);
+        }
+        let owner = self.env().caller();
+        self.assert_withdraw_size(&owner, side, tokens);
+        if self.redemption_value(&owner, side, tokens) < U256::from(MIN_ORDER_VALUE) {
+            self.env().revert(MarketError::OrderTooSmall);
+        }
+        if !self.orders.has_room_for(&owner) {
+            self.env().revert(MarketError::TooManyOpenOrders);
+        }
+        let market = self.env().self_address();
+        self.move_tokens(side, &owner, &market, tokens);
+        self.orders.add(owner, side, tokens, trigger, trigger_price)
+    }
+
+    /// Cancels an open order and returns the escrowed tokens to its owner.
+    #[odra(non_reentrant)]
+    pub fn cancel_order(&mut self, id: u32) {
+        let order = self.get_order(id);
+        if order.owner != self.env().caller() {
+            self.env().revert(MarketError::NotOrderOwner);
+        }
+        if order.status != OrderStatus::Open {
+            self.env().revert(MarketError::OrderNotOpen);
+        }
+        self.release_escrow(order, OrderStatus::Cancelled);
+    }
+
+    /// Executes at most `max` orders triggered at the current price. Anyone
+    /// can call it and is rewarded with the execution fees of the orders.
+    /// Returns the number of executed orders.
+    #[odra(non_reentrant)]
+    pub fn execute_orders(&mut self, max: u32) -> u32 {
+        self.assert_not_paused();
+        let executed = self.execute_triggered_orders(max);
+        let reward = self.keepers.config().order_execution_reward * executed;
+        self.reward_keeper(&self.env().caller(), reward);
+        executed
+    }
+
+    pub fn get_order(&self, id: u32) -> Order {
+        self.orders
+            .get(id)
+            .unwrap_or_revert_with(&self.env(), MarketError::OrderNotFound)
+    }
+
+    pub fn get_open_orders(&self) -> Vec<Order> {
+        self.orders.open_orders()
+    }
+
+    pub fn get_open_orders_of(&self, owner: &Address) -> Vec<Order> {
+        self.orders
+            .open_orders()
+            .into_iter()
+            .filter(|order| &order.owner == owner)
+            .collect()
+    }
+
+    /// Settles at most `max` queued trades left by the expiry. Every other
+    /// trade is settled by the first price update after it. Anyone can
+    /// call it. Returns the number of settled trades.
+    #[odra(non_reentrant)]
+    pub fn settle_requests(&mut self, max: u32) -> u32 {
+        if self.expiry.is_expired() {
+            // No price comes after the expiry, so the queued trades settle
+            // at the final one.
+            self.settlement.close_round();
+        }
+        self.settle_ready_requests(max)
+    }
+
+    /// Sends the settled tokens and collateral to the caller.
+    #[odra(non_reentrant)]
+    pub fn claim(&mut self) {
+        let caller = self.env().caller();
+        let claimable = self.settlement.take_claimable(&caller);
+        if claimable.is_empty() {
+            self.env().revert(MarketError::NothingToClaim);
+        }
+        if !claimable.long.is_zero() {
+            self.cfg.long_token().mint(&caller, &claimable.long);
+        }
+        if !claimable.short.is_zero() {
+            self.cfg.short_token().mint(&caller, &claimable.short);
+        }
+        if !claimable.collateral.is_zero() {
+            self.withdraw_deposit(&caller, &claimable.collateral);
+        }
+    }
+
+    pub fn get_claimable(&self, account: &Address) -> Claimable {
+        self.settlement.claimable(account)
+    }
+
+    pub fn get_pending_requests(&self) -> Vec<PendingRequest> {
+        self.settlement.pending()
+    }
+
+    pub fn get_pending_requests_of(&self, account: &Address) -> Vec<PendingRequest> {
+        self.settlement
+            .pending()
+            .into_iter()
+            .filter(|request| &request.account == account)
+            .collect()
+    }
+
+    /// Turns deferred settlement on or off. Trades already queued are
+    /// settled at the next price either way.
+    #[odra(non_reentrant)]
+    pub fn set_deferred_settlement(&mut self, enabled: bool) {
+        self.admin.assert_owner(&self.env().caller());
+        self.settlement.set_enabled(enabled);
+    }
+
+    pub fn is_deferred_settlement(&self) -> bool {
+        self.settlement.is_enabled()
     }
 
     pub fn get_market_state(&self) -> MarketState {
         self.get_state()
     }
 
+    /// Returns the tokens minted and the fee paid for depositing `amount`
+    /// WCSPR into the given side at the current state.
+    pub fn preview_deposit(&self, side: Side, amount: U256) -> TradePreview {
+        let (amount, fee) = split_fee(amount);
+        let mut state = self.get_state();
+        self.assert_not_wiped_out(&state, side);
+        let tokens_out = state.on_deposit(side, amount);
+        TradePreview {
+            amount_out: tokens_out,
+            fee,
+            early_exit_fee: U256::zero(),
+        }
+    }
+
+    /// Returns the LONG and SHORT holdings of `address`, including tokens
+    /// escrowed by open orders, with their current redemption value.
+    pub fn get_position(&self, address: &Address) -> Position {
+        let state = self.get_state();
+        let long_balance = self.balance_of(address, Side::Long);
+        let short_balance = self.balance_of(address, Side::Short);
+        Position {
+            long_balance,
+            short_balance,
+            long_value: self.redemption_value(address, Side::Long, long_balance),
+            short_value: self.redemption_value(address, Side::Short, short_balance),
+            long_share_price: state.share_price(Side::Long),
+            short_share_price: state.share_price(Side::Short),
+            long_pool_share: state.pool_share(Side::Long, long_balance),
+            short_pool_share: state.pool_share(Side::Short, short_balance),
+        }
+    }
+
+    /// Returns the WCSPR paid out and the fees paid for withdrawing `tokens`
+    /// of the given side held by `account` at the current state, including
+    /// the early-exit fee of `account`.
+    pub fn preview_withdraw(&self, account: &Address, side: Side, tokens: U256) -> TradePreview {
+        let mut state = self.get_state();
+        let collateral = state.on_withdraw(side, tokens);
+        let (collateral_out, fee, early_exit_fee) =
+            self.charge_withdrawal_fees(&mut state, account, side, collateral);
+        TradePreview {
+            amount_out: collateral_out,
+            fee,
+            early_exit_fee,
+        }
+    }
+
+    /// Returns the entry price, unrealized and realized profit or loss of
+    /// `address` on both sides.
+    pub fn get_pnl(&self, address: &Address) -> PnlReport {
+        PnlReport {
+            long: self.side_pnl(address, Side::Long),
+            short: self.side_pnl(address, Side::Short),
+        }
+    }
+
+    /// Moves the cost basis of LONG or SHORT tokens transferred between
+    /// accounts. Called by the token contracts before the transfer.
+    #[odra(non_reentrant)]
+    pub fn on_token_transfer(&mut self, sender: &Address, recipient: &Address, amount: U256) {
+        let caller = self.env().caller();
+        let cfg = self.cfg.get();
+        let side = if cfg.is_long_token(&caller) {
+            Side::Long
+        } else if cfg.is_short_token(&caller) {
+            Side::Short
+        } else {
+            self.env().revert(MarketError::NotATokenContract)
+        };
+        if sender == recipient {
+            return;
+        }
+        let recipient_balance = self.balance_of(recipient, side);
+        let limits = self.get_limits();
+        if limits.exceeds_account_share(&self.get_state(), side, recipient_balance + amount) {
+            self.env().revert(MarketError::AccountShareExceeded);
+        }
+        self.pnl
+            .on_transfer(sender, recipient, side, recipient_balance, amount);
+    }
+
+    /// Sets the first config. Later changes go through `propose_config`.
+    #[odra(non_reentrant)]
     pub fn set_config(&mut self, cfg: Config) {
         self.admin.assert_owner(&self.env().caller());
+        if self.cfg.try_get().is_some() {
+            self.env().revert(TimelockError::ConfigAlreadySet);
+        }
+        self.cfg.set(cfg);
+    }
+
+    /// Proposes a new config, executable once the config delay has passed.
+    /// Returns the ETA of the change.
+    #[odra(non_reentrant)]
+    pub fn propose_config(&mut self, cfg: Config) -> u64 {
+        self.admin.assert_owner(&self.env().caller());
+        self.timelock.propose(cfg)
+    }
+
+    /// Applies the proposed config after its ETA. Anyone can call it.
+    #[odra(non_reentrant)]
+    pub fn execute_config(&mut self) {
+        let cfg = self.timelock.take_ready();
         self.cfg.set(cfg);
     }
+
+    #[odra(non_reentrant)]
+    pub fn cancel_config(&mut self) {
+        self.admin.assert_owner(&self.env().caller());
+        self.timelock.cancel();
+    }
+
+    pub fn get_pending_config(&self) -> Option<PendingConfig> {
+        self.timelock.pending()
+    }
+
+    /// Raises the delay of config changes.
+    #[odra(non_reentrant)]
+    pub fn set_config_delay(&mut self, delay: u64) {
+        self.admin.assert_owner(&self.env().caller());
+        self.timelock.set_delay(delay);
+    }
+
+    pub fn get_config_delay(&self) -> u64 {
+        self.timelock.delay()
+    }
+
+    /// Brings the stored state to the current layout after new code is
+    /// installed.
+    #[odra(non_reentrant)]
+    pub fn migrate(&mut self) {
+        self.admin.assert_owner(&self.env().caller());
+        let from = self.version.start_migration();
+        self.version.finish_migration(from);
+    }
+
+    pub fn get_state_version(&self) -> u32 {
+        self.version.get()
+    }
+
+    pub fn get_config(&self) -> Option<Config> {
+        self.cfg.try_get()
+    }
+
+    delegate! {
+        to self.admin {
+            /// Hands the owner rights over, e.g. to a multisig.
+            fn transfer_ownership(&mut self, new_owner: &Address);
+
+            /// Returns the owner of the contract.
+            fn get_owner(&self) -> Address;
+        }
+    }
+
+    #[odra(non_reentrant)]
+    pub fn set_rewards_destination(&mut self, destination: RewardsDestination) {
+        self.admin.assert_owner(&self.env().caller());
+        self.rewards_destination.set(destination);
+    }
+
+    #[odra(non_reentrant)]
+    pub fn set_limits(&mut self, limits: MarketLimits) {
+        self.admin.assert_owner(&self.env().caller());
+        self.rules.set_limits(limits);
+    }
+
+    pub fn get_limits(&self) -> MarketLimits {
+        self.rules.limits()
+    }
+
+    #[odra(non_reentrant)]
+    pub fn set_imbalance_guard(&mut self, guard: ImbalanceGuard) {
+        self.admin.assert_owner(&self.env().caller());
+        if !guard.is_valid() {
+            self.env().revert(MarketError::InvalidImbalanceRatio);
+        }
+        self.rules.set_imbalance_guard(guard);
+    }
+
+    pub fn get_imbalance_guard(&self) -> ImbalanceGuard {
+        self.rules.imbalance_guard()
+    }
+
+    /// Sets the extra fee for withdrawals within the holding period after
+    /// the last deposit.
+    #[odra(non_reentrant)]
+    pub fn set_early_exit_fee(&mut self, early_exit_fee: EarlyExitFee) {
+        self.admin.assert_owner(&self.env().caller());
+        if !early_exit_fee.is_valid() {
+            self.env().revert(MarketError::EarlyExitFeeTooHigh);
+        }
+        self.rules.set_early_exit_fee(early_exit_fee);
+    }
+
+    pub fn get_early_exit_fee(&self) -> EarlyExitFee {
+        self.rules.early_exit_fee()
+    }
+
+    /// Stops deposits, withdrawals, flips and order executions in an
+    /// emergency. Trades queued before the pause still settle at the next
+    /// price.
+    #[odra(non_reentrant)]
+    pub fn pause(&mut self) {
+        self.admin.assert_owner(&self.env().caller());
+        self.rules.set_paused(true);
+    }
+
+    #[odra(non_reentrant)]
+    pub fn unpause(&mut self) {
+        self.admin.assert_owner(&self.env().caller());
+        self.rules.set_paused(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.rules.is_paused()
+    }
+
+    /// Returns the block time from which `account` can exit the side
+    /// without the early-exit fee.
+    pub fn get_exit_unlock_time(&self, account: &Address, side: Side) -> u64 {
+        self.get_early_exit_fee()
+            .unlock_time(self.last_deposit_of(account, side))
+    }
+
+    /// Returns the largest WCSPR deposit the side can accept at the current
+    /// state, fee included. Returns `U256::MAX` if the side is not limited.
+    pub fn get_deposit_capacity(&self, side: Side) -> U256 {
+        if self.expiry.is_expired() {
+            return U256::zero();
+        }
+        let state = self.get_state();
+        let capacity = [
+            self.get_limits().side_capacity(&state, side),
+            self.get_imbalance_guard().capacity(&state, side),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        match capacity {
+            Some(capacity) => gross_for_net(capacity),
+            None => U256::MAX,
+        }
+    }
+
+    #[odra(non_reentrant)]
+    pub fn set_keeper(&mut self, account: &Address, is_keeper: bool) {
+        self.admin.assert_owner(&self.env().caller());
+        self.keepers.set_keeper(account, is_keeper);
+    }
+
+    pub fn is_keeper(&self, account: &Address) -> bool {
+        self.keepers.is_keeper(account)
+    }
+
+    #[odra(non_reentrant)]
+    pub fn set_keeper_config(&mut self, config: KeeperConfig) {
+        self.admin.assert_owner(&self.env().caller());
+        if !config.is_valid() {
+            self.env().revert(MarketError::KeeperFeeShareTooHigh);
+        }
+        self.keepers.set_config(config);
+    }
+
+    pub fn get_keeper_config(&self) -> KeeperConfig {
+        self.keepers.config()
+    }
+
+    /// Returns the total rewards paid to `keeper`.
+    pub fn get_keeper_earnings(&self, keeper: &Address) -> U256 {
+        self.keepers.earnings(keeper)
+    }
+
+    /// Returns the fees set aside for keeper rewards.
+    pub fn get_keeper_pool(&self) -> U256 {
+        self.keepers.pool()
+    }
+
+    /// Sets the share of the fees, left after the keepers' share, that is
+    /// added to the insurance fund. In basis points.
+    #[odra(non_reentrant)]
+    pub fn set_insurance_fee_share(&mut self, fee_share: u64) {
+        self.admin.assert_owner(&self.env().caller());
+        if fee_share > BASIS_POINTS {
+            self.env().revert(MarketError::InsuranceFeeShareTooHigh);
+        }
+        self.insurance.set_fee_share(fee_share);
+    }
+
+    pub fn get_insurance_fee_share(&self) -> u64 {
+        self.insurance.fee_share()
+    }
+
+    /// Returns the WCSPR held by the insurance fund.
+    pub fn get_insurance_fund(&self) -> U256 {
+        self.insurance.balance()
+    }
+
+    /// Returns all the payouts made by the insurance fund, oldest first.
+    pub fn get_insurance_payouts(&self) -> Vec<Payout> {
+        self.insurance.payouts()
+    }
+
+    /// Returns how much WCSPR the market is missing to cover the recorded
+    /// liquidity, the keeper pool, the insurance fund and the collateral of
+    /// queued trades.
+    pub fn get_accounting_deficit(&self) -> U256 {
+        let recorded = self.get_state().total_liquidity()
+            + self.keepers.pool()
+            + self.insurance.balance()
+            + self.settlement.held_collateral();
+        let held = self
+            .cfg
+            .wcspr_token()
+            .balance_of(&self.env().self_address());
+        recorded.saturating_sub(held)
+    }
+
+    /// Covers the accounting deficit from the insurance fund. Anyone can
+    /// call it. Returns the amount covered.
+    #[odra(non_reentrant)]
+    pub fn cover_deficit(&mut self) -> U256 {
+        let deficit = self.get_accounting_deficit();
+        if deficit.is_zero() {
+            return deficit;
+        }
+        let block_time = self.env().get_block_time();
+        // The fund's WCSPR is already in the market, so writing it off
+        // is enough to cover the missing liquidity.
+        self.insurance
+            .pay(PayoutReason::Deficit, deficit, block_time)
+    }
+
+    pub fn get_rewards_destination(&self) -> RewardsDestination {
+        self.rewards_destination.get_or_default()
+    }
+
+    /// Distributes staking rewards earned by the pooled collateral.
+    ///
+    /// Rewards are taken from the caller and either shared by both sides pro
+    /// rata or sent to the fee collector. Rewards of an empty pool or of an
+    /// expired market, whose claims are fixed, always go to the fee
+    /// collector.
+    #[odra(non_reentrant)]
+    pub fn distribute_rewards(&mut self, amount: U256) {
+        let caller = self.env().caller();
+        self.admin.assert_owner(&caller);
+        self.collect_deposit(&caller, &amount);
+
+        let mut state = self.get_state();
+        match self.get_rewards_destination() {
+            RewardsDestination::Holders
+                if !state.total_liquidity().is_zero() && !self.expiry.is_expired() =>
+            {
+                state.on_rewards(amount);
+                self.set_state(state);
+            }
+            _ => self.send_to_fee_collector(&amount),
+        }
+    }
 }
 
 impl Market {
@@ -95,53 +894,471 @@ impl Market {
         self.state.set(state);
     }
 
-    fn deposit_unchecked(&mut self, sender: &Address, side: Side, amount: U256) {
-        self.collect_deposit(&sender, &amount);
+    /// Moves the liquidity to the new price. If the losing side can't pay
+    /// the full move, the insurance fund tops up the winning side.
+    fn apply_price(&mut self, price: U256) {
+        let mut state = self.get_state();
+        let winning_side = state.winning_side(price);
+        let shortfall = state.on_price_change(&self.get_payoff(), price);
+        if !shortfall.is_zero() {
+            let block_time = self.env().get_block_time();
+            let paid = self
+                .insurance
+                .pay(PayoutReason::CappedMove, shortfall, block_time);
+            state.add_liquidity(winning_side, paid);
+        }
+        self.set_state(state);
+    }
+
+    fn deposit_unchecked(&mut self, sender: &Address, side: Side, amount: U256) -> U256 {
+        self.deposit_for_unchecked(sender, sender, side, amount)
+    }
+
+    /// Takes `amount` WCSPR from `payer` and mints the tokens to `recipient`.
+    fn deposit_for_unchecked(
+        &mut self,
+        payer: &Address,
+        recipient: &Address,
+        side: Side,
+        amount: U256,
+    ) -> U256 {
+        self.assert_not_paused();
+        self.expiry.assert_not_expired();
+        if self.settlement.is_enabled() {
+            self.queue_deposit(payer, recipient, side, amount);
+            return U256::zero();
+        }
+        let amount = self.accept_deposit(side, amount);
+        if self.get_limits().is_deposit_too_small(amount) {
+            self.env().revert(MarketError::DepositTooSmall);
+        }
+        self.collect_deposit(payer, &amount);
+        let paid = amount;
         let (amount, fee) = split_fee(amount);
         self.collect_fee(&fee);
 
+        let balance = self.balance_of(recipient, side);
         let mut state = self.get_state();
+        self.assert_not_wiped_out(&state, side);
         let new_tokens = state.on_deposit(side, amount);
+        self.assert_deposit_limits(&state, side, balance + new_tokens);
         self.set_state(state);
 
-        // Mint new tokens to the caller.
+        self.pnl.on_deposit(recipient, side, balance, new_tokens, paid);
+
+        // Mint new tokens to the recipient.
         match side {
-            Side::Long => self.cfg.long_token().mint(&sender, &new_tokens),
-            Side::Short => self.cfg.short_token().mint(&sender, &new_tokens),
+            Side::Long => self.cfg.long_token().mint(recipient, &new_tokens),
+            Side::Short => self.cfg.short_token().mint(recipient, &new_tokens),
         };
+
+        new_tokens
+    }
+
+    pub fn withdrawal_unchecked(&mut self, reciever: &Address, side: Side, amount: U256) -> U256 {
+        self.assert_withdraw_size(reciever, side, amount);
+        self.withdraw_or_queue(reciever, reciever, side, amount)
+    }
+
+    /// Settles the withdrawal now or queues it if the settlement is
+    /// deferred and the market not expired. Returns the WCSPR paid out now.
+    fn withdraw_or_queue(
+        &mut self,
+        holder: &Address,
+        reciever: &Address,
+        side: Side,
+        amount: U256,
+    ) -> U256 {
+        self.assert_not_paused();
+        if !self.settlement.is_enabled() || self.expiry.is_expired() {
+            return self.settle_withdrawal(holder, reciever, side, amount);
+        }
+        self.assert_queue_has_room(holder);
+        let market = self.env().self_address();
+        self.move_tokens(side, holder, &market, amount);
+        self.settlement
+            .add(*holder, *reciever, side, RequestKind::Withdraw, amount);
+        U256::zero()
+    }
+
+    /// Burns `amount` tokens of `holder` and pays the collateral to
+    /// `reciever`.
+    fn settle_withdrawal(
+        &mut self,
+        holder: &Address,
+        reciever: &Address,
+        side: Side,
+        amount: U256,
+    ) -> U256 {
+        let withdraw_amount = self.redeem(holder, holder, side, amount);
+        self.withdraw_deposit(reciever, &withdraw_amount);
+        withdraw_amount
     }
 
-    pub fn withdrawal_unchecked(&mut self, reciever: &Address, side: Side, amount: U256) {
+    /// Burns `amount` tokens owned by `owner` and returns the WCSPR owed for
+    /// them after the fees. The PnL is booked on `holder`.
+    fn redeem(&mut self, holder: &Address, owner: &Address, side: Side, amount: U256) -> U256 {
         // Update the state and get the amount that can be withdrawn.
         let mut state = self.get_state();
         let withdraw_amount = state.on_withdraw(side, amount);
+        let (withdraw_amount, fee, _) =
+            self.charge_withdrawal_fees(&mut state, holder, side, withdraw_amount);
         self.set_state(state);
 
-        // Withdraw the deposit and fee.
-        let (withdraw_amount, fee) = split_fee(withdraw_amount);
+        // Collect the fee.
         self.collect_fee(&fee);
-        self.withdraw_deposit(reciever, &withdraw_amount);
+        self.pnl.on_withdraw(holder, side, amount, withdraw_amount);
 
         // Burn the tokens.
         match side {
-            Side::Long => self.cfg.long_token().burn(&reciever, &amount),
-            Side::Short => self.cfg.short_token().burn(&reciever, &amount),
+            Side::Long => self.cfg.long_token().burn(owner, &amount),
+            Side::Short => self.cfg.short_token().burn(owner, &amount),
+        };
+
+        withdraw_amount
+    }
+
+    /// Takes `amount` WCSPR from `payer` and queues the deposit.
+    fn queue_deposit(&mut self, payer: &Address, recipient: &Address, side: Side, amount: U256) {
+        if self.get_limits().is_deposit_too_small(amount) {
+            self.env().revert(MarketError::DepositTooSmall);
+        }
+        self.assert_queue_has_room(payer);
+        self.collect_deposit(payer, &amount);
+        self.settlement
+            .add(*payer, *recipient, side, RequestKind::Deposit, amount);
+    }
+
+    fn settle_ready_requests(&mut self, max: u32) -> u32 {
+        let requests = self.settlement.take_ready(max);
+        let settled = requests.len() as u32;
+        for request in requests {
+            match request.kind {
+                RequestKind::Deposit => self.settle_queued_deposit(request),
+                RequestKind::Withdraw => self.settle_queued_withdrawal(request),
+            }
+        }
+        settled
+    }
+
+    /// Credits the tokens of a queued deposit to the recipient, who mints
+    /// them with a claim. Deposits rejected by the limits, into a wiped out
+    /// side or settled after the expiry are refunded to the payer.
+    fn settle_queued_deposit(&mut self, request: PendingRequest) {
+        let (payer, recipient, side) = (request.account, request.recipient, request.side);
+        if self.expiry.is_expired() || self.get_state().is_wiped_out(side) {
+            self.settlement.credit_collateral(&payer, request.amount);
+            return;
+        }
+        let filled = self
+            .fillable_deposit(side, request.amount)
+            .unwrap_or_default();
+        let (amount, fee) = split_fee(filled);
+
+        let balance = self.balance_of(&recipient, side);
+        let mut state = self.get_state();
+        let new_tokens = state.on_deposit(side, amount);
+        if filled.is_zero()
+            || self
+                .deposit_limits_error(&state, side, balance + new_tokens)
+                .is_some()
+        {
+            self.settlement.credit_collateral(&payer, request.amount);
+            return;
+        }
+        self.set_state(state);
+        self.collect_fee(&fee);
+        self.pnl
+            .on_deposit(&recipient, side, balance, new_tokens, filled);
+        self.settlement.credit_tokens(&recipient, side, new_tokens);
+        if filled < request.amount {
+            self.settlement
+                .credit_collateral(&payer, request.amount - filled);
+        }
+    }
+
+    /// Burns the tokens of a queued withdrawal, held by the market, and
+    /// credits the collateral to the recipient.
+    fn settle_queued_withdrawal(&mut self, request: PendingRequest) {
+        let market = self.env().self_address();
+        let withdraw_amount = self.redeem(&request.account, &market, request.side, request.amount);
+        self.settlement
+            .credit_collateral(&request.recipient, withdraw_amount);
+    }
+
+    /// Takes the early-exit and the trading fee from the `collateral`
+    /// withdrawn by `holder`. Returns the rest, the trading fee and the
+    /// early-exit fee. An expired market charges neither.
+    fn charge_withdrawal_fees(
+        &self,
+        state: &mut MarketState,
+        holder: &Address,
+        side: Side,
+        collateral: U256,
+    ) -> (U256, U256, U256) {
+        if self.expiry.is_expired() {
+            return (collateral, U256::zero(), U256::zero());
+        }
+        let early_exit_fee = self.early_exit_fee_of(holder, side, collateral);
+        state.add_liquidity(side.opposite(), early_exit_fee);
+        let (collateral, fee) = split_fee(collateral - early_exit_fee);
+        (collateral, fee, early_exit_fee)
+    }
+
+    /// Takes the early-exit and the flip fee from the `collateral` of the
+    /// `side_from` tokens flipped by `holder`. Returns the rest, the flip fee
+    /// and the early-exit fee.
+    ///
+    /// The early-exit fee goes to the holders left on `side_from`, as the
+    /// flipper joins the opposite side. If nobody is left, it is collected
+    /// with the flip fee, so the next deposit into `side_from` doesn't get
+    /// it for free.
+    fn charge_flip_fees(
+        &self,
+        state: &mut MarketState,
+        holder: &Address,
+        side_from: Side,
+        collateral: U256,
+    ) -> (U256, U256, U256) {
+        let early_exit_fee = self.early_exit_fee_of(holder, side_from, collateral);
+        let (collateral, mut fee) =
+            split_flip_fee(collateral - early_exit_fee, self.get_flip_fee());
+        if state.token_supply(side_from).is_zero() {
+            fee += early_exit_fee;
+        } else {
+            state.add_liquidity(side_from, early_exit_fee);
+        }
+        (collateral, fee, early_exit_fee)
+    }
+
+    /// Early-exit fee of `holder` on the `collateral` of its `side` tokens.
+    fn early_exit_fee_of(&self, holder: &Address, side: Side, collateral: U256) -> U256 {
+        let last_deposit = self.last_deposit_of(holder, side);
+        self.get_early_exit_fee()
+            .fee_of(collateral, last_deposit, self.env().get_block_time())
+    }
+
+    fn last_deposit_of(&self, holder: &Address, side: Side) -> u64 {
+        match side {
+            Side::Long => self.cfg.long_token().last_deposit_of(holder),
+            Side::Short => self.cfg.short_token().last_deposit_of(holder),
+        }
+    }
+
+    /// Moves LONG or SHORT tokens without the transfer hook.
+    fn move_tokens(&self, side: Side, from: &Address, to: &Address, tokens: U256) {
+        match side {
+            Side::Long => self.cfg.long_token().transfer_from(from, to, &tokens),
+            Side::Short => self.cfg.short_token().transfer_from(from, to, &tokens),
+        };
+    }
+
+    fn execute_triggered_orders(&mut self, max: u32) -> u32 {
+        let price = self.get_state().price;
+        let orders = self.orders.triggered(price, max);
+        let executed = orders.len() as u32;
+        let execution_fee = self.keepers.config().order_execution_reward;
+        for order in orders {
+            let (owner, side, tokens) = (order.owner, order.side, order.tokens);
+            self.release_escrow(order, OrderStatus::Executed);
+            // The size was checked when the order was placed.
+            let collateral = self.redeem(&owner, &owner, side, tokens);
+            // The owner pays for the execution, so orders can't be used to
+            // drain the keeper pool.
+            let fee = collateral.min(execution_fee);
+            self.keepers.add_to_pool(fee);
+            self.pnl.on_sale_fee(&owner, side, fee);
+            self.withdraw_deposit(&owner, &(collateral - fee));
+        }
+        executed
+    }
+
+    /// Closes the order and returns the escrowed tokens to its owner.
+    fn release_escrow(&mut self, order: Order, status: OrderStatus) {
+        let market = self.env().self_address();
+        self.move_tokens(order.side, &market, &order.owner, order.tokens);
+        self.orders.close(order, status);
+    }
+
+    /// Returns the part of the deposit the imbalance guard accepts.
+    fn accept_deposit(&self, side: Side, amount: U256) -> U256 {
+        self.fillable_deposit(side, amount)
+            .unwrap_or_revert_with(&self.env(), MarketError::ImbalanceExceeded)
+    }
+
+    /// Returns the part of the deposit the imbalance guard accepts, or
+    /// `None` if the deposit is rejected.
+    fn fillable_deposit(&self, side: Side, amount: U256) -> Option<U256> {
+        let guard = self.get_imbalance_guard();
+        let Some(capacity) = guard.capacity(&self.get_state(), side) else {
+            return Some(amount);
+        };
+        let capacity = gross_for_net(capacity);
+        if amount <= capacity {
+            return Some(amount);
+        }
+        match guard.mode {
+            ImbalanceMode::PartialFill if !capacity.is_zero() => Some(capacity),
+            _ => None,
+        }
+    }
+
+    /// Checks the side and the account holding `balance` tokens against the
+    /// limits after a deposit.
+    fn assert_deposit_limits(&self, state: &MarketState, side: Side, balance: U256) {
+        if let Some(error) = self.deposit_limits_error(state, side, balance) {
+            self.env().revert(error);
+        }
+    }
+
+    fn deposit_limits_error(
+        &self,
+        state: &MarketState,
+        side: Side,
+        balance: U256,
+    ) -> Option<MarketError> {
+        if self.get_imbalance_guard().is_exceeded(state, side) {
+            return Some(MarketError::ImbalanceExceeded);
+        }
+        let limits = self.get_limits();
+        if limits.exceeds_side_liquidity(state, side) {
+            return Some(MarketError::SideLiquidityCapExceeded);
+        }
+        if limits.exceeds_account_share(state, side, balance) {
+            return Some(MarketError::AccountShareExceeded);
+        }
+        None
+    }
+
+    /// Trades settle immediately unless deferred, and always once the
+    /// market expired.
+    fn assert_not_paused(&self) {
+        if self.rules.is_paused() {
+            self.env().revert(MarketError::MarketPaused);
+        }
+    }
+
+    fn assert_immediate_settlement(&self) {
+        if self.settlement.is_enabled() && !self.expiry.is_expired() {
+            self.env().revert(MarketError::SettlementIsDeferred);
+        }
+    }
+
+    fn assert_queue_has_room(&self, account: &Address) {
+        if !self.settlement.has_room_for(account) {
+            self.env().revert(MarketError::TooManyQueuedRequests);
+        }
+    }
+
+    fn assert_not_wiped_out(&self, state: &MarketState, side: Side) {
+        if state.is_wiped_out(side) {
+            self.env().revert(MarketError::SideWipedOut);
+        }
+    }
+
+    fn assert_withdraw_size(&self, holder: &Address, side: Side, tokens: U256) {
+        let balance = match side {
+            Side::Long => self.cfg.long_token().balance_of(holder),
+            Side::Short => self.cfg.short_token().balance_of(holder),
         };
+        if self.get_limits().is_withdraw_too_small(tokens, balance) {
+            self.env().revert(MarketError::WithdrawTooSmall);
+        }
+    }
+
+    fn assert_valid_recipient(&self, recipient: &Address) {
+        let cfg = self.cfg.get();
+        if cfg.is_long_token(recipient)
+            || cfg.is_short_token(recipient)
+            || cfg.is_wcspr_token(recipient)
+            || cfg.is_market(recipient)
+        {
+            self.env().revert(MarketError::InvalidRecipient);
+        }
+    }
+
+    /// Tokens of `address`, including the ones escrowed by open orders and
+    /// held by the settlement queue.
+    fn balance_of(&self, address: &Address, side: Side) -> U256 {
+        let escrowed =
+            self.orders.escrowed(address, side) + self.settlement.held_tokens(address, side);
+        match side {
+            Side::Long => self.cfg.long_token().balance_of(address) + escrowed,
+            Side::Short => self.cfg.short_token().balance_of(address) + escrowed,
+        }
+    }
+
+    fn side_pnl(&self, address: &Address, side: Side) -> SidePnl {
+        let basis = self.pnl.get(address, side);
+        let balance = self.balance_of(address, side);
+        SidePnl {
+            entry_price: basis.entry_price,
+            unrealized: Pnl::new(
+                self.redemption_value(address, side, balance),
+                basis.cost_of(balance),
+            ),
+            realized: basis.realized,
+        }
+    }
+
+    fn redemption_value(&self, holder: &Address, side: Side, tokens: U256) -> U256 {
+        if tokens.is_zero() {
+            return U256::zero();
+        }
+        self.preview_withdraw(holder, side, tokens).amount_out
+    }
+
+    fn assert_deadline(&self, deadline: u64) {
+        if self.env().get_block_time() > deadline {
+            self.env().revert(MarketError::DeadlineExpired);
+        }
+    }
+
+    fn assert_min_tokens_out(&self, tokens: U256, min_tokens_out: U256) {
+        if tokens < min_tokens_out {
+            self.env().revert(MarketError::InsufficientTokensOut);
+        }
+    }
+
+    fn assert_min_collateral_out(&self, collateral: U256, min_collateral_out: U256) {
+        if collateral < min_collateral_out {
+            self.env().revert(MarketError::InsufficientCollateralOut);
+        }
     }
 
     // Check if the new price is in fact newer and if so, update the last price.
-    // fn handle_and_validate_new_price(&mut self, new: PriceData) {
-    //     let current = self.last_price.get_or_revert_with(MarketError::LastPriceNotSet);
-    //     if current.timestamp > new.timestamp {
-    //         self.env().revert(MarketError::NewPriceIsTooOld);
-    //     }
-    //     if new.timestamp > self.env().get_block_time() {
-    //         self.env().revert(MarketError::NewPriceIsFromTheFuture);
-    //     }
-    //     self.last_price.set(new);
-    // }
+    // Returns true if the price is strictly newer than the last one.
+    fn handle_and_validate_new_price(&mut self, new: PriceData) -> bool {
+        let current = self.get_last_price();
+        if current.timestamp > new.timestamp {
+            self.env().revert(MarketError::NewPriceIsTooOld);
+        }
+        if new.timestamp > self.env().get_block_time() {
+            self.env().revert(MarketError::NewPriceIsFromTheFuture);
+        }
+        let is_fresh = new.timestamp > current.timestamp;
+        self.last_price.set(new);
+        is_fresh
+    }
+
+    fn reward_keeper(&mut self, keeper: &Address, amount: U256) {
+        if amount.is_zero() {
+            return;
+        }
+        let block_time = self.env().get_block_time();
+        let reward = self.keepers.reward(keeper, amount, block_time);
+        if !reward.is_zero() {
+            self.cfg.wcspr_token().transfer(keeper, &reward);
+        }
+    }
 
     fn collect_fee(&mut self, amount: &U256) {
+        let amount = self.keepers.take_fee_share(*amount);
+        let amount = self.insurance.take_fee_share(amount);
+        self.send_to_fee_collector(&amount);
+    }
+
+    fn send_to_fee_collector(&mut self, amount: &U256) {
         let fee_collector = self.cfg.fee_collector();
         self.cfg
             .wcspr_token()
@@ -151,7 +1368,7 @@ impl Market {
     fn collect_deposit(&mut self, sender: &Address, amount: &U256) {
         self.cfg
             .wcspr_token()
-            .transfer_from(&sender, &self.env().self_address(), amount);
+            .transfer_from(sender, &self.env().self_address(), amount);
     }
 
     fn withdraw_deposit(&mut self, recipient: &Address, amount: &U256) {
@@ -159,12 +1376,76 @@ impl Market {
     }
 }
 
+/// Orders executed by a single price update. The remaining triggered orders
+/// can be executed with `execute_orders`.
+pub static MAX_ORDERS_PER_PRICE_UPDATE: u32 = 10;
+
+/// Fee of deposits and withdrawals, in basis points.
+pub static TRADING_FEE: u64 = 50;
+
 pub fn split_fee(amount: U256) -> (U256, U256) {
     let fee = amount / U256::from(200);
     let amount = amount - fee;
     (amount, fee)
 }
 
+/// Returns the largest deposit that adds at most `net` liquidity after the
+/// trading fee.
+pub fn gross_for_net(net: U256) -> U256 {
+    let mut gross = net * U256::from(200) / U256::from(199);
+    while split_fee(gross).0 > net {
+        gross -= U256::one();
+    }
+    gross
+}
+
+/// Splits `amount` into the flipped amount and the fee of `fee` basis points.
+pub fn split_flip_fee(amount: U256, fee: u64) -> (U256, U256) {
+    let fee = amount * U256::from(fee) / U256::from(BASIS_POINTS);
+    (amount - fee, fee)
+}
+
+/// Expected outcome of a deposit or a withdrawal.
+#[odra::odra_type]
+pub struct TradePreview {
+    /// Tokens minted on deposit or WCSPR paid out on withdrawal.
+    pub amount_out: U256,
+    /// Fee sent to the fee collector.
+    pub fee: U256,
+    /// Early-exit fee left in the pool, zero for deposits.
+    pub early_exit_fee: U256,
+}
+
+/// Holdings of a single account.
+#[odra::odra_type]
+pub struct Position {
+    pub long_balance: U256,
+    pub short_balance: U256,
+    /// WCSPR received for redeeming all LONG tokens, after fees.
+    pub long_value: U256,
+    /// WCSPR received for redeeming all SHORT tokens, after fees.
+    pub short_value: U256,
+    /// WCSPR backing one whole LONG token.
+    pub long_share_price: U256,
+    /// WCSPR backing one whole SHORT token.
+    pub short_share_price: U256,
+    /// Share of the long pool, in basis points.
+    pub long_pool_share: U256,
+    /// Share of the short pool, in basis points.
+    pub short_pool_share: U256,
+}
+
+/// Where the staking rewards of the pooled collateral go.
+#[odra::odra_type]
+#[derive(Default)]
+pub enum RewardsDestination {
+    /// Shared by the long and short side proportionally to their liquidity.
+    #[default]
+    Holders,
+    /// Sent to the fee collector.
+    Treasury,
+}
+
 #[odra::odra_error]
 pub enum MarketError {
     LastPriceNotSet = 8001,
@@ -172,8 +1453,151 @@ pub enum MarketError {
     NewPriceIsFromTheFuture = 8003,
     LongShareNotSet = 8004,
     TotalDepositNotSet = 8005,
-    LongTokenContractNotACallerOnDeposit = 8006,
-    ShortTokenContractNotACallerOnDeposit = 8007,
+    WCSPRTokenContractNotACallerOnLongDeposit = 8006,
+    WCSPRTokenContractNotACallerOnShortDeposit = 8007,
     LongTokenContractNotACallerOnWithdrawal = 8008,
     ShortTokenContractNotACallerOnWithdrawal = 8009,
+    DeadlineExpired = 8010,
+    InsufficientTokensOut = 8011,
+    InsufficientCollateralOut = 8012,
+    NotATokenContract = 8013,
+    InvalidOrderAmount = 8014,
+    OrderNotFound = 8015,
+    NotOrderOwner = 8016,
+    OrderNotOpen = 8017,
+    FlipFeeTooHigh = 8018,
+    InvalidRecipient = 8019,
+    DepositTooSmall = 8020,
+    WithdrawTooSmall = 8021,
+    SideLiquidityCapExceeded = 8022,
+    AccountShareExceeded = 8023,
+    ImbalanceExceeded = 8024,
+    InvalidImbalanceRatio = 8025,
+    InsuranceFeeShareTooHigh = 8026,
+    SettlementIsDeferred = 8027,
+    NothingToClaim = 8028,
+    EarlyExitFeeTooHigh = 8029,
+    InvalidPayoff = 8030,
+    OrderTooSmall = 8031,
+    TooManyOpenOrders = 8032,
+    KeeperFeeShareTooHigh = 8033,
+    RequestsPending = 8034,
+    SideWipedOut = 8035,
+    TooManyQueuedRequests = 8036,
+    MarketPaused = 8037,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expiry::ExpiryError;
+    use odra::host::Deployer;
+
+    type Hook = fn(&mut MarketHostRef, &Address, U256) -> OdraResult<()>;
+
+    /// Deploys a market whose config points at accounts, so any config
+    /// address can be used as the caller.
+    fn setup() -> (odra::host::HostEnv, MarketHostRef, Config) {
+        let env = odra_test::env();
+        let mut market = Market::deploy(
+            &env,
+            MarketInitArgs {
+                last_price: PriceData {
+                    price: U256::from(1000),
+                    timestamp: 0,
+                },
+                expiry: None,
+                payoff: Payoff::Linear,
+            },
+        );
+        let config = Config {
+            long_token: env.get_account(1),
+            short_token: env.get_account(2),
+            wcspr_token: env.get_account(3),
+            fee_collector: env.get_account(4),
+            market: env.get_account(5),
+        };
+        market.set_config(config.clone());
+        (env, market, config)
+    }
+
+    fn check_hook(hook: Hook, allowed: fn(&Config) -> Address, error: MarketError) {
+        let (env, mut market, config) = setup();
+        let victim = env.get_account(6);
+        let error: OdraError = error.into();
+        let allowed = allowed(&config);
+        for index in 0..8 {
+            let caller = env.get_account(index);
+            env.set_caller(caller);
+            let result = hook(&mut market, &victim, U256::from(100));
+            if caller == allowed {
+                assert_ne!(result.err(), Some(error.clone()));
+            } else {
+                assert_eq!(result.err(), Some(error.clone()), "caller {}", index);
+            }
+        }
+    }
+
+    #[test]
+    fn expiry_must_be_in_the_future() {
+        let env = odra_test::env();
+        env.advance_block_time(100);
+        let result = Market::try_deploy(
+            &env,
+            MarketInitArgs {
+                last_price: PriceData {
+                    price: U256::from(1000),
+                    timestamp: 0,
+                },
+                expiry: Some(100),
+                payoff: Payoff::Linear,
+            },
+        );
+        assert_eq!(result.err(), Some(ExpiryError::InvalidExpiry.into()));
+    }
+
+    #[test]
+    fn payoff_must_be_valid() {
+        let env = odra_test::env();
+        let result = Market::try_deploy(
+            &env,
+            MarketInitArgs {
+                last_price: PriceData {
+                    price: U256::from(1000),
+                    timestamp: 0,
+                },
+                expiry: None,
+                payoff: Payoff::LeveragedLinear { leverage: 0 },
+            },
+        );
+        assert_eq!(result.err(), Some(MarketError::InvalidPayoff.into()));
+    }
+
+    #[test]
+    fn deposit_hooks_accept_only_the_wcspr_token() {
+        check_hook(
+            |market, sender, amount| market.try_deposit_long_from(sender, amount),
+            |config| config.wcspr_token,
+            MarketError::WCSPRTokenContractNotACallerOnLongDeposit,
+        );
+        check_hook(
+            |market, sender, amount| market.try_deposit_short_from(sender, amount),
+            |config| config.wcspr_token,
+            MarketError::WCSPRTokenContractNotACallerOnShortDeposit,
+        );
+    }
+
+    #[test]
+    fn withdrawal_hooks_accept_only_their_token() {
+        check_hook(
+            |market, sender, amount| market.try_withdraw_long_from(sender, amount),
+            |config| config.long_token,
+            MarketError::LongTokenContractNotACallerOnWithdrawal,
+        );
+        check_hook(
+            |market, sender, amount| market.try_withdraw_short_from(sender, amount),
+            |config| config.short_token,
+            MarketError::ShortTokenContractNotACallerOnWithdrawal,
+        );
+    }
 }
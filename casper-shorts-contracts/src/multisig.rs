@@ -0,0 +1,249 @@
+//! M-of-N multisig for the privileged operations.
+//!
+//! Installed as the owner of the Market and the tokens, it calls any of
+//! their entry points once enough signers have approved the call. The call
+//! is stored as the target contract, the entry point and the serialized
+//! runtime args.
+use odra::{
+    casper_types::{
+        bytesrepr::{self, Bytes, FromBytes},
+        RuntimeArgs,
+    },
+    module::Module,
+    prelude::*,
+    CallDef,
+};
+
+/// Call waiting for approvals.
+#[odra::odra_type]
+pub struct Proposal {
+    pub id: u32,
+    pub proposer: Address,
+    pub target: Address,
+    pub entry_point: String,
+    /// Serialized `RuntimeArgs` of the call.
+    pub args: Bytes,
+    pub executed: bool,
+}
+
+#[odra::event]
+pub struct ProposalCreated {
+    pub id: u32,
+    pub proposer: Address,
+    pub target: Address,
+    pub entry_point: String,
+}
+
+#[odra::event]
+pub struct ProposalApproved {
+    pub id: u32,
+    pub signer: Address,
+}
+
+#[odra::event]
+pub struct ApprovalRevoked {
+    pub id: u32,
+    pub signer: Address,
+}
+
+#[odra::event]
+pub struct ProposalExecuted {
+    pub id: u32,
+    pub executor: Address,
+}
+
+#[odra::event]
+pub struct SignersChanged {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[odra::module(events = [
+    ProposalCreated,
+    ProposalApproved,
+    ApprovalRevoked,
+    ProposalExecuted,
+    SignersChanged
+])]
+pub struct Multisig {
+    signers: Var<Vec<Address>>,
+    threshold: Var<u32>,
+    last_id: Var<u32>,
+    proposals: Mapping<u32, Proposal>,
+    approvals: Mapping<(u32, Address), bool>,
+}
+
+#[odra::module]
+impl Multisig {
+    pub fn init(&mut self, signers: Vec<Address>, threshold: u32) {
+        self.change_signers(signers, threshold);
+    }
+
+    /// Proposes calling `entry_point` of `target` with the serialized
+    /// `args`. The proposer's approval is counted. Returns the id of the
+    /// proposal.
+    pub fn propose(&mut self, target: Address, entry_point: String, args: Bytes) -> u32 {
+        let proposer = self.assert_signer();
+        if RuntimeArgs::from_bytes(&args).is_err() {
+            self.env().revert(MultisigError::InvalidArgs);
+        }
+
+        let id = self.last_id.get_or_default() + 1;
+        self.last_id.set(id);
+        self.proposals.set(
+            &id,
+            Proposal {
+                id,
+                proposer,
+                target,
+                entry_point: entry_point.clone(),
+                args,
+                executed: false,
+            },
+        );
+        self.env().emit_event(ProposalCreated {
+            id,
+            proposer,
+            target,
+            entry_point,
+        });
+        self.approve(id);
+        id
+    }
+
+    pub fn approve(&mut self, id: u32) {
+        let signer = self.assert_signer();
+        self.open_proposal(id);
+        if self.is_approved_by(id, &signer) {
+            self.env().revert(MultisigError::AlreadyApproved);
+        }
+        self.approvals.set(&(id, signer), true);
+        self.env().emit_event(ProposalApproved { id, signer });
+    }
+
+    pub fn revoke_approval(&mut self, id: u32) {
+        let signer = self.assert_signer();
+        self.open_proposal(id);
+        if !self.is_approved_by(id, &signer) {
+            self.env().revert(MultisigError::NotApproved);
+        }
+        self.approvals.set(&(id, signer), false);
+        self.env().emit_event(ApprovalRevoked { id, signer });
+    }
+
+    /// Makes the proposed call once the threshold is reached. Any signer
+    /// can execute it.
+    pub fn execute(&mut self, id: u32) {
+        let executor = self.assert_signer();
+        let mut proposal = self.open_proposal(id);
+        if self.approval_count(id) < self.get_threshold() {
+            self.env().revert(MultisigError::NotEnoughApprovals);
+        }
+
+        proposal.executed = true;
+        self.proposals.set(&id, proposal.clone());
+        let args = RuntimeArgs::from_bytes(&proposal.args)
+            .map(|(args, _)| args)
+            .unwrap_or_revert_with(&self.env(), MultisigError::InvalidArgs);
+        let call = CallDef::new(proposal.entry_point, true, args);
+        self.env().call_contract::<AnyResult>(proposal.target, call);
+        self.env().emit_event(ProposalExecuted { id, executor });
+    }
+
+    /// Replaces the signers and the threshold. Only callable by the
+    /// multisig itself, so it needs a proposal too.
+    pub fn set_signers(&mut self, signers: Vec<Address>, threshold: u32) {
+        if self.env().caller() != self.env().self_address() {
+            self.env().revert(MultisigError::CallerNotMultisig);
+        }
+        self.change_signers(signers, threshold);
+    }
+
+    pub fn get_signers(&self) -> Vec<Address> {
+        self.signers.get_or_default()
+    }
+
+    pub fn get_threshold(&self) -> u32 {
+        self.threshold.get_or_default()
+    }
+
+    pub fn get_proposal(&self, id: u32) -> Proposal {
+        self.proposals
+            .get(&id)
+            .unwrap_or_revert_with(&self.env(), MultisigError::ProposalNotFound)
+    }
+
+    /// Returns the current signers that approved the proposal.
+    pub fn get_approvals(&self, id: u32) -> Vec<Address> {
+        self.get_signers()
+            .into_iter()
+            .filter(|signer| self.is_approved_by(id, signer))
+            .collect()
+    }
+}
+
+impl Multisig {
+    fn change_signers(&mut self, signers: Vec<Address>, threshold: u32) {
+        if threshold == 0 || threshold as usize > signers.len() {
+            self.env().revert(MultisigError::InvalidThreshold);
+        }
+        for (i, signer) in signers.iter().enumerate() {
+            if signers[..i].contains(signer) {
+                self.env().revert(MultisigError::DuplicateSigner);
+            }
+        }
+        self.signers.set(signers.clone());
+        self.threshold.set(threshold);
+        self.env().emit_event(SignersChanged { signers, threshold });
+    }
+
+    fn assert_signer(&self) -> Address {
+        let caller = self.env().caller();
+        if !self.get_signers().contains(&caller) {
+            self.env().revert(MultisigError::NotASigner);
+        }
+        caller
+    }
+
+    fn open_proposal(&self, id: u32) -> Proposal {
+        let proposal = self.get_proposal(id);
+        if proposal.executed {
+            self.env().revert(MultisigError::ProposalAlreadyExecuted);
+        }
+        proposal
+    }
+
+    fn is_approved_by(&self, id: u32, signer: &Address) -> bool {
+        self.approvals.get_or_default(&(id, *signer))
+    }
+
+    /// Counts the approvals of the current signers only, so removed signers
+    /// don't count.
+    fn approval_count(&self, id: u32) -> u32 {
+        self.get_approvals(id).len() as u32
+    }
+}
+
+/// Return value of a proposed call. Entry points return different types, so
+/// the bytes are taken as they are and dropped.
+struct AnyResult;
+
+impl FromBytes for AnyResult {
+    fn from_bytes(_bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        Ok((AnyResult, &[]))
+    }
+}
+
+#[odra::odra_error]
+pub enum MultisigError {
+    NotASigner = 12001,
+    InvalidThreshold = 12002,
+    DuplicateSigner = 12003,
+    ProposalNotFound = 12004,
+    AlreadyApproved = 12005,
+    NotApproved = 12006,
+    ProposalAlreadyExecuted = 12007,
+    NotEnoughApprovals = 12008,
+    InvalidArgs = 12009,
+    CallerNotMultisig = 12010,
+}
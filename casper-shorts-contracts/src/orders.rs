@@ -0,0 +1,195 @@
+//! Conditional exit orders executed when the price crosses a trigger.
+use odra::{casper_types::U256, prelude::*};
+
+use crate::system::Side;
+
+/// Price movement that makes an order executable.
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum Trigger {
+    /// Executes when the price is at or below the trigger price.
+    Below,
+    /// Executes when the price is at or above the trigger price.
+    Above,
+}
+
+impl Trigger {
+    pub fn is_hit(&self, price: U256, trigger_price: U256) -> bool {
+        match self {
+            Trigger::Below => price <= trigger_price,
+            Trigger::Above => price >= trigger_price,
+        }
+    }
+}
+
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum OrderStatus {
+    Open,
+    Executed,
+    Cancelled,
+}
+
+/// Request to close `tokens` of the given side once the trigger is hit.
+#[odra::odra_type]
+pub struct Order {
+    pub id: u32,
+    pub owner: Address,
+    pub side: Side,
+    /// LONG or SHORT tokens held in escrow by the market.
+    pub tokens: U256,
+    pub trigger: Trigger,
+    pub trigger_price: U256,
+    pub status: OrderStatus,
+}
+
+impl Order {
+    pub fn is_triggered(&self, price: U256) -> bool {
+        self.trigger.is_hit(price, self.trigger_price)
+    }
+}
+
+/// Smallest WCSPR value of the tokens of an order, so the book can't be
+/// filled with dust orders. 1 WCSPR.
+pub static MIN_ORDER_VALUE: u64 = 1_000_000_000;
+
+/// Most open orders of a single account.
+pub static MAX_OPEN_ORDERS_PER_ACCOUNT: u32 = 10;
+
+/// Most open orders of the market, so a price update can load the book
+/// within the gas limit.
+pub static MAX_OPEN_ORDERS: u32 = 100;
+
+/// Open orders of one trigger, sorted so the ones triggered first come
+/// first. Each entry is the trigger price and the order id.
+type Queue = Vec<(U256, u32)>;
+
+#[odra::module]
+pub struct OrderBook {
+    last_id: Var<u32>,
+    orders: Mapping<u32, Order>,
+    /// `Below` orders by descending trigger price.
+    below: Var<Queue>,
+    /// `Above` orders by ascending trigger price.
+    above: Var<Queue>,
+    open_count: Mapping<Address, u32>,
+    escrowed: Mapping<(Address, Side), U256>,
+}
+
+impl OrderBook {
+    /// Returns false if the book or the owner's open orders are full.
+    pub fn has_room_for(&self, owner: &Address) -> bool {
+        self.open_count() < MAX_OPEN_ORDERS
+            && self.open_count.get_or_default(owner) < MAX_OPEN_ORDERS_PER_ACCOUNT
+    }
+
+    pub fn add(
+        &mut self,
+        owner: Address,
+        side: Side,
+        tokens: U256,
+        trigger: Trigger,
+        trigger_price: U256,
+    ) -> u32 {
+        let id = self.last_id.get_or_default() + 1;
+        self.last_id.set(id);
+        self.orders.set(
+            &id,
+            Order {
+                id,
+                owner,
+                side,
+                tokens,
+                trigger,
+                trigger_price,
+                status: OrderStatus::Open,
+            },
+        );
+
+        // Orders with the same trigger price keep the placement order.
+        let mut queue = self.queue(trigger);
+        let position = queue.partition_point(|(price, _)| match trigger {
+            Trigger::Below => *price >= trigger_price,
+            Trigger::Above => *price <= trigger_price,
+        });
+        queue.insert(position, (trigger_price, id));
+        self.set_queue(trigger, queue);
+
+        let count = self.open_count.get_or_default(&owner);
+        self.open_count.set(&owner, count + 1);
+        let escrowed = self.escrowed(&owner, side);
+        self.escrowed.set(&(owner, side), escrowed + tokens);
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<Order> {
+        self.orders.get(&id)
+    }
+
+    /// Marks an open order as executed or cancelled and releases its escrow.
+    pub fn close(&mut self, mut order: Order, status: OrderStatus) {
+        let mut queue = self.queue(order.trigger);
+        queue.retain(|(_, id)| *id != order.id);
+        self.set_queue(order.trigger, queue);
+
+        let count = self.open_count.get_or_default(&order.owner);
+        self.open_count.set(&order.owner, count - 1);
+        let escrowed = self.escrowed(&order.owner, order.side);
+        self.escrowed
+            .set(&(order.owner, order.side), escrowed - order.tokens);
+
+        let id = order.id;
+        order.status = status;
+        self.orders.set(&id, order);
+    }
+
+    /// Returns the open orders, oldest first.
+    pub fn open_orders(&self) -> Vec<Order> {
+        let mut ids: Vec<u32> = self
+            .queue(Trigger::Below)
+            .into_iter()
+            .chain(self.queue(Trigger::Above))
+            .map(|(_, id)| id)
+            .collect();
+        ids.sort();
+        ids.iter().filter_map(|id| self.orders.get(id)).collect()
+    }
+
+    /// Returns at most `max` open orders triggered at `price`, the ones
+    /// triggered first first. Only the triggered orders are loaded.
+    pub fn triggered(&self, price: U256, max: u32) -> Vec<Order> {
+        [Trigger::Below, Trigger::Above]
+            .into_iter()
+            .flat_map(|trigger| {
+                self.queue(trigger)
+                    .into_iter()
+                    .take_while(move |(trigger_price, _)| trigger.is_hit(price, *trigger_price))
+            })
+            .take(max as usize)
+            .filter_map(|(_, id)| self.orders.get(&id))
+            .collect()
+    }
+
+    /// Tokens of `owner` held in escrow by open orders.
+    pub fn escrowed(&self, owner: &Address, side: Side) -> U256 {
+        self.escrowed.get_or_default(&(*owner, side))
+    }
+
+    fn open_count(&self) -> u32 {
+        (self.queue(Trigger::Below).len() + self.queue(Trigger::Above).len()) as u32
+    }
+
+    fn queue(&self, trigger: Trigger) -> Queue {
+        match trigger {
+            Trigger::Below => self.below.get_or_default(),
+            Trigger::Above => self.above.get_or_default(),
+        }
+    }
+
+    fn set_queue(&mut self, trigger: Trigger, queue: Queue) {
+        match trigger {
+            Trigger::Below => self.below.set(queue),
+            Trigger::Above => self.above.set(queue),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Cost basis tracking used to report profit and loss of the positions.
+use odra::{casper_types::U256, prelude::*};
+
+use crate::system::{Side, ONE_TOKEN};
+
+/// Profit or loss denominated in WCSPR. At most one of the fields is
+/// non-zero.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct Pnl {
+    pub profit: U256,
+    pub loss: U256,
+}
+
+impl Pnl {
+    /// Result of receiving `value` for something that cost `cost`.
+    pub fn new(value: U256, cost: U256) -> Self {
+        if value >= cost {
+            Pnl {
+                profit: value - cost,
+                loss: U256::zero(),
+            }
+        } else {
+            Pnl {
+                profit: U256::zero(),
+                loss: cost - value,
+            }
+        }
+    }
+
+    pub fn add(&self, other: &Pnl) -> Pnl {
+        Pnl::new(self.profit + other.profit, self.loss + other.loss)
+    }
+}
+
+/// Entry information of an account's position on one side.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct CostBasis {
+    /// Weighted-average WCSPR paid for one whole token, fees included.
+    pub entry_price: U256,
+    /// Cumulative profit or loss of all withdrawals.
+    pub realized: Pnl,
+}
+
+impl CostBasis {
+    /// WCSPR paid for `tokens` at the entry price.
+    pub fn cost_of(&self, tokens: U256) -> U256 {
+        self.entry_price * tokens / U256::from(ONE_TOKEN)
+    }
+}
+
+/// Profit and loss of an account's position on one side.
+#[odra::odra_type]
+pub struct SidePnl {
+    pub entry_price: U256,
+    pub unrealized: Pnl,
+    pub realized: Pnl,
+}
+
+#[odra::odra_type]
+pub struct PnlReport {
+    pub long: SidePnl,
+    pub short: SidePnl,
+}
+
+#[odra::module]
+pub struct PnlTracker {
+    cost_basis: Mapping<(Address, Side), CostBasis>,
+}
+
+impl PnlTracker {
+    pub fn get(&self, account: &Address, side: Side) -> CostBasis {
+        self.cost_basis.get_or_default(&(*account, side))
+    }
+
+    /// Records `tokens` bought for `paid` WCSPR by an account that held
+    /// `balance` tokens before.
+    pub fn on_deposit(
+        &mut self,
+        account: &Address,
+        side: Side,
+        balance: U256,
+        tokens: U256,
+        paid: U256,
+    ) {
+        let mut basis = self.get(account, side);
+        basis.entry_price = weighted_entry_price(basis.cost_of(balance), paid, balance + tokens);
+        self.cost_basis.set(&(*account, side), basis);
+    }
+
+    /// Records `tokens` sold for `received` WCSPR.
+    pub fn on_withdraw(&mut self, account: &Address, side: Side, tokens: U256, received: U256) {
+        let mut basis = self.get(account, side);
+        let pnl = Pnl::new(received, basis.cost_of(tokens));
+        basis.realized = basis.realized.add(&pnl);
+        self.cost_basis.set(&(*account, side), basis);
+    }
+
+    /// Records `fee` WCSPR taken from the proceeds of a sale after it was
+    /// recorded, e.g. the reward for executing an order.
+    pub fn on_sale_fee(&mut self, account: &Address, side: Side, fee: U256) {
+        let mut basis = self.get(account, side);
+        basis.realized = basis.realized.add(&Pnl::new(U256::zero(), fee));
+        self.cost_basis.set(&(*account, side), basis);
+    }
+
+    /// Moves `tokens` between accounts at the sender's entry price. The
+    /// recipient held `recipient_balance` tokens before the transfer.
+    pub fn on_transfer(
+        &mut self,
+        sender: &Address,
+        recipient: &Address,
+        side: Side,
+        recipient_balance: U256,
+        tokens: U256,
+    ) {
+        let sender_basis = self.get(sender, side);
+        let mut recipient_basis = self.get(recipient, side);
+        recipient_basis.entry_price = weighted_entry_price(
+            recipient_basis.cost_of(recipient_balance),
+            sender_basis.cost_of(tokens),
+            recipient_balance + tokens,
+        );
+        self.cost_basis.set(&(*recipient, side), recipient_basis);
+    }
+}
+
+fn weighted_entry_price(old_cost: U256, new_cost: U256, total_tokens: U256) -> U256 {
+    if total_tokens.is_zero() {
+        return U256::zero();
+    }
+    (old_cost + new_cost) * U256::from(ONE_TOKEN) / total_tokens
+}
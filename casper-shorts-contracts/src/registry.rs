@@ -0,0 +1,123 @@
+//! Registry of fully wired markets.
+//!
+//! This is a registry, not a factory: market creation is not atomic. Odra
+//! 1.5 contracts can't deploy other contracts, so a market is created in
+//! two parts. The client's `deploy-market` command deploys the Market, LONG
+//! and SHORT contracts and wires them. The registry then checks the wiring
+//! and records the market, so only complete markets are listed. That
+//! includes the WCSPR route: the token must deposit transfers to the LONG and
+//! SHORT tokens into the market, either as its config or as a market added
+//! with `add_market` after the config delay.
+//!
+//! Between the two parts a half-wired market may exist on chain, but it is
+//! never listed. Integrators must discover markets through the registry and
+//! not through deploy events.
+use odra::{module::Module, prelude::*, ContractRef};
+use odra_modules::access::Ownable;
+
+use crate::{
+    config::Config, market::MarketContractRef, token_long::TokenLongContractRef,
+    token_short::TokenShortContractRef, token_wcspr::TokenWCSPRContractRef,
+};
+
+/// Market recorded in the registry.
+#[odra::odra_type]
+pub struct MarketEntry {
+    pub id: u32,
+    /// Addresses of the market contracts, the collateral and the fee
+    /// collector.
+    pub config: Config,
+    /// Keeper pushing the prices to the market.
+    pub price_feed: Address,
+    pub created_at: u64,
+    /// Expiry of a dated market, `None` for a perpetual one.
+    pub expiry: Option<u64>,
+}
+
+#[odra::module]
+pub struct MarketRegistry {
+    admin: SubModule<Ownable>,
+    markets: List<MarketEntry>,
+    registered: Mapping<Address, bool>,
+}
+
+#[odra::module]
+impl MarketRegistry {
+    pub fn init(&mut self) {
+        self.admin.init();
+    }
+
+    /// Records the market after checking that the Market, LONG and SHORT
+    /// contracts all use `config` and that `price_feed` is a keeper of the
+    /// market. Returns the id of the market.
+    ///
+    /// CEP-18 security badges can't be read by other contracts, so the
+    /// Market's minter rights are granted by the client and not checked.
+    pub fn register_market(&mut self, config: Config, price_feed: Address) -> u32 {
+        self.admin.assert_owner(&self.env().caller());
+        if self.is_registered(&config.market) {
+            self.env().revert(RegistryError::MarketAlreadyRegistered);
+        }
+
+        let env = self.env();
+        let market = MarketContractRef::new(env.clone(), config.market);
+        if market.get_config().as_ref() != Some(&config) {
+            env.revert(RegistryError::MarketNotConfigured);
+        }
+        let long_token = TokenLongContractRef::new(env.clone(), config.long_token);
+        if long_token.get_config().as_ref() != Some(&config) {
+            env.revert(RegistryError::LongTokenNotConfigured);
+        }
+        let short_token = TokenShortContractRef::new(env.clone(), config.short_token);
+        if short_token.get_config().as_ref() != Some(&config) {
+            env.revert(RegistryError::ShortTokenNotConfigured);
+        }
+        let wcspr = TokenWCSPRContractRef::new(env.clone(), config.wcspr_token);
+        if !wcspr.routes_to(config.clone()) {
+            env.revert(RegistryError::WcsprNotRouted);
+        }
+        if !market.is_keeper(&price_feed) {
+            env.revert(RegistryError::PriceFeedNotKeeper);
+        }
+
+        let id = self.markets.len();
+        self.registered.set(&config.market, true);
+        self.markets.push(MarketEntry {
+            id,
+            config,
+            price_feed,
+            created_at: env.get_block_time(),
+            expiry: market.get_expiry(),
+        });
+        id
+    }
+
+    pub fn is_registered(&self, market: &Address) -> bool {
+        self.registered.get_or_default(market)
+    }
+
+    pub fn get_market(&self, id: u32) -> MarketEntry {
+        self.markets
+            .get(id)
+            .unwrap_or_revert_with(&self.env(), RegistryError::MarketNotFound)
+    }
+
+    pub fn get_markets(&self) -> Vec<MarketEntry> {
+        self.markets.iter().collect()
+    }
+
+    pub fn get_market_count(&self) -> u32 {
+        self.markets.len()
+    }
+}
+
+#[odra::odra_error]
+pub enum RegistryError {
+    MarketAlreadyRegistered = 10001,
+    MarketNotConfigured = 10002,
+    LongTokenNotConfigured = 10003,
+    ShortTokenNotConfigured = 10004,
+    PriceFeedNotKeeper = 10005,
+    MarketNotFound = 10006,
+    WcsprNotRouted = 10007,
+}
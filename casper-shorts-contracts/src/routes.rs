@@ -0,0 +1,148 @@
+//! Markets the WCSPR token routes deposits to, besides the one of its
+//! config.
+//!
+//! A WCSPR transfer to the LONG or SHORT token of a routed market deposits
+//! into that market, and routed markets pull WCSPR without an allowance like
+//! the configured one. As that lets a market move any holder's WCSPR, a
+//! route is proposed first and added only after the config delay.
+use odra::{module::Module, prelude::*};
+
+use crate::{config::Config, system::Side, timelock::PendingConfig};
+
+/// Market and side a LONG or SHORT token deposits into.
+#[odra::odra_type]
+pub struct MarketRoute {
+    pub market: Address,
+    pub side: Side,
+}
+
+#[odra::event]
+pub struct MarketRouteProposed {
+    pub market: Address,
+    pub long_token: Address,
+    pub short_token: Address,
+    pub eta: u64,
+}
+
+#[odra::event]
+pub struct MarketRouteAdded {
+    pub market: Address,
+    pub long_token: Address,
+    pub short_token: Address,
+}
+
+#[odra::event]
+pub struct MarketRouteCancelled {
+    pub market: Address,
+}
+
+#[odra::module(events = [MarketRouteProposed, MarketRouteAdded, MarketRouteCancelled])]
+pub struct MarketRoutes {
+    /// Proposed routes by market.
+    proposed: Mapping<Address, Option<PendingConfig>>,
+    /// Routes by LONG and SHORT token.
+    routes: Mapping<Address, MarketRoute>,
+    markets: Mapping<Address, bool>,
+}
+
+impl MarketRoutes {
+    /// Stores the route of `config` until `delay` passes and returns its
+    /// ETA. Markets are proposed independently of each other.
+    pub fn propose(&mut self, config: Config, delay: u64) -> u64 {
+        if self.proposed(&config.market).is_some() {
+            self.env().revert(RouteError::RouteAlreadyProposed);
+        }
+        if self.is_market(&config.market)
+            || self.route(&config.long_token).is_some()
+            || self.route(&config.short_token).is_some()
+        {
+            self.env().revert(RouteError::AlreadyRouted);
+        }
+        let eta = self.env().get_block_time() + delay;
+        self.env().emit_event(MarketRouteProposed {
+            market: config.market,
+            long_token: config.long_token,
+            short_token: config.short_token,
+            eta,
+        });
+        let market = config.market;
+        self.proposed
+            .set(&market, Some(PendingConfig { config, eta }));
+        eta
+    }
+
+    /// Adds the proposed route of `market`, reverting if its ETA hasn't
+    /// passed yet.
+    pub fn add(&mut self, market: &Address) {
+        let pending = self.take_proposed(market);
+        if !pending.is_ready(self.env().get_block_time()) {
+            self.env().revert(RouteError::RouteNotReady);
+        }
+        let config = pending.config;
+        self.routes.set(
+            &config.long_token,
+            MarketRoute {
+                market: config.market,
+                side: Side::Long,
+            },
+        );
+        self.routes.set(
+            &config.short_token,
+            MarketRoute {
+                market: config.market,
+                side: Side::Short,
+            },
+        );
+        self.markets.set(&config.market, true);
+        self.env().emit_event(MarketRouteAdded {
+            market: config.market,
+            long_token: config.long_token,
+            short_token: config.short_token,
+        });
+    }
+
+    pub fn cancel(&mut self, market: &Address) {
+        self.take_proposed(market);
+        self.env()
+            .emit_event(MarketRouteCancelled { market: *market });
+    }
+
+    pub fn proposed(&self, market: &Address) -> Option<PendingConfig> {
+        self.proposed.get(market).flatten()
+    }
+
+    pub fn route(&self, token: &Address) -> Option<MarketRoute> {
+        self.routes.get(token)
+    }
+
+    pub fn is_market(&self, address: &Address) -> bool {
+        self.markets.get_or_default(address)
+    }
+
+    /// Whether the LONG and SHORT tokens of `config` deposit into its
+    /// market.
+    pub fn routes_to(&self, config: &Config) -> bool {
+        let routes_side = |token: &Address, side: Side| {
+            self.route(token)
+                .is_some_and(|route| route.market == config.market && route.side == side)
+        };
+        routes_side(&config.long_token, Side::Long) && routes_side(&config.short_token, Side::Short)
+    }
+
+    fn take_proposed(&mut self, market: &Address) -> PendingConfig {
+        let pending = self
+            .proposed(market)
+            .unwrap_or_revert_with(&self.env(), RouteError::NoProposedRoute);
+        self.proposed.set(market, None);
+        pending
+    }
+}
+
+#[odra::odra_error]
+pub enum RouteError {
+    RouteAlreadyProposed = 15001,
+    AlreadyRouted = 15002,
+    NoProposedRoute = 15003,
+    RouteNotReady = 15004,
+    NotThisToken = 15005,
+}
@@ -0,0 +1,270 @@
+//! Deferred settlement of deposits and withdrawals.
+//!
+//! When enabled, trades are queued and settled at the price of the next
+//! `set_price`, so a price visible before it reaches the market can't be
+//! traded on. Settled tokens and collateral are claimed afterwards.
+//!
+//! The queue is capped at what a single `set_price` settles, so every trade
+//! settles at the first price after it was made and never at a later one.
+use odra::{casper_types::U256, prelude::*};
+
+use crate::system::Side;
+
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum RequestKind {
+    /// WCSPR paid in, tokens to mint.
+    Deposit,
+    /// Tokens held by the market, WCSPR to pay out.
+    Withdraw,
+}
+
+/// Trade waiting for the next price.
+#[odra::odra_type]
+pub struct PendingRequest {
+    pub id: u32,
+    pub account: Address,
+    /// Account credited with the settled tokens or collateral.
+    pub recipient: Address,
+    pub side: Side,
+    pub kind: RequestKind,
+    /// WCSPR of a deposit or tokens of a withdrawal.
+    pub amount: U256,
+    /// Price round the request was made in.
+    pub round: u64,
+}
+
+/// Tokens and collateral ready to be claimed.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct Claimable {
+    pub long: U256,
+    pub short: U256,
+    pub collateral: U256,
+}
+
+impl Claimable {
+    pub fn is_empty(&self) -> bool {
+        self.long.is_zero() && self.short.is_zero() && self.collateral.is_zero()
+    }
+}
+
+/// Most queued trades of a single account.
+pub static MAX_QUEUED_REQUESTS_PER_ACCOUNT: u32 = 5;
+
+/// Most queued trades of the market, all settled by the next price update.
+pub static MAX_QUEUED_REQUESTS: u32 = 20;
+
+#[odra::module]
+pub struct SettlementQueue {
+    enabled: Var<bool>,
+    round: Var<u64>,
+    last_id: Var<u32>,
+    requests: Mapping<u32, PendingRequest>,
+    /// Ids of the requests made in each round, oldest first.
+    round_requests: Mapping<u64, Vec<u32>>,
+    pending_tokens: Mapping<(Address, Side), U256>,
+    claimable: Mapping<Address, Claimable>,
+    held_collateral: Var<U256>,
+    collateral_swept: Var<bool>,
+    /// Oldest round that may still have requests.
+    first_round: Var<u64>,
+    queued_count: Mapping<Address, u32>,
+    queued_total: Var<u32>,
+}
+
+impl SettlementQueue {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get_or_default()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    /// Starts a new price round. Requests of the previous rounds become
+    /// ready to settle.
+    pub fn next_round(&mut self) {
+        self.round.set(self.round.get_or_default() + 1);
+    }
+
+    /// Starts a new round if the current one has requests, so they become
+    /// ready to settle without a new price.
+    pub fn close_round(&mut self) {
+        let round = self.round.get_or_default();
+        if !self.round_requests.get_or_default(&round).is_empty() {
+            self.next_round();
+        }
+    }
+
+    /// Returns false if the queue or the account's queued trades are full.
+    pub fn has_room_for(&self, account: &Address) -> bool {
+        self.queued_total.get_or_default() < MAX_QUEUED_REQUESTS
+            && self.queued_count.get_or_default(account) < MAX_QUEUED_REQUESTS_PER_ACCOUNT
+    }
+
+    pub fn add(
+        &mut self,
+        account: Address,
+        recipient: Address,
+        side: Side,
+        kind: RequestKind,
+        amount: U256,
+    ) -> u32 {
+        let id = self.last_id.get_or_default() + 1;
+        let round = self.round.get_or_default();
+        self.last_id.set(id);
+        self.requests.set(
+            &id,
+            PendingRequest {
+                id,
+                account,
+                recipient,
+                side,
+                kind,
+                amount,
+                round,
+            },
+        );
+
+        let total = self.queued_total.get_or_default();
+        if total == 0 {
+            self.first_round.set(round);
+        }
+        self.queued_total.set(total + 1);
+        let count = self.queued_count.get_or_default(&account);
+        self.queued_count.set(&account, count + 1);
+        let mut ids = self.round_requests.get_or_default(&round);
+        ids.push(id);
+        self.round_requests.set(&round, ids);
+
+        match kind {
+            RequestKind::Deposit => self.hold_collateral(amount),
+            RequestKind::Withdraw => {
+                let tokens = self.pending_tokens(&account, side);
+                self.pending_tokens.set(&(account, side), tokens + amount);
+            }
+        }
+        id
+    }
+
+    /// Removes and returns at most `max` requests made before the current
+    /// round, oldest first.
+    pub fn take_ready(&mut self, max: u32) -> Vec<PendingRequest> {
+        let round = self.round.get_or_default();
+        let mut first_round = self.first_round.get_or_default();
+        let mut ready = Vec::new();
+        while first_round < round && ready.len() < max as usize {
+            let mut ids = self.round_requests.get_or_default(&first_round);
+            let taken = ids.len().min(max as usize - ready.len());
+            ready.extend(ids.drain(..taken).filter_map(|id| self.requests.get(&id)));
+            let is_done = ids.is_empty();
+            self.round_requests.set(&first_round, ids);
+            if !is_done {
+                break;
+            }
+            first_round += 1;
+        }
+
+        let total = self.queued_total.get_or_default() - ready.len() as u32;
+        self.queued_total.set(total);
+        // Skip the empty rounds up to the current one.
+        self.first_round
+            .set(if total == 0 { round } else { first_round });
+
+        for request in ready.iter() {
+            let count = self.queued_count.get_or_default(&request.account);
+            self.queued_count.set(&request.account, count - 1);
+            match request.kind {
+                RequestKind::Deposit => self.release_collateral(request.amount),
+                RequestKind::Withdraw => {
+                    let key = (request.account, request.side);
+                    let tokens = self.pending_tokens(&request.account, request.side);
+                    self.pending_tokens.set(&key, tokens - request.amount);
+                }
+            }
+        }
+        ready
+    }
+
+    pub fn pending(&self) -> Vec<PendingRequest> {
+        if self.queued_total.get_or_default() == 0 {
+            return Vec::new();
+        }
+        (self.first_round.get_or_default()..=self.round.get_or_default())
+            .flat_map(|round| self.round_requests.get_or_default(&round))
+            .filter_map(|id| self.requests.get(&id))
+            .collect()
+    }
+
+    /// Tokens of `account` held by pending withdrawals.
+    pub fn pending_tokens(&self, account: &Address, side: Side) -> U256 {
+        self.pending_tokens.get_or_default(&(*account, side))
+    }
+
+    pub fn credit_tokens(&mut self, account: &Address, side: Side, tokens: U256) {
+        let mut claimable = self.claimable(account);
+        match side {
+            Side::Long => claimable.long += tokens,
+            Side::Short => claimable.short += tokens,
+        }
+        self.claimable.set(account, claimable);
+    }
+
+    pub fn credit_collateral(&mut self, account: &Address, amount: U256) {
+        let mut claimable = self.claimable(account);
+        claimable.collateral += amount;
+        self.claimable.set(account, claimable);
+        self.hold_collateral(amount);
+    }
+
+    /// Tokens and collateral `account` can claim. Collateral swept with
+    /// the market is not claimable anymore.
+    pub fn claimable(&self, account: &Address) -> Claimable {
+        let mut claimable = self.claimable.get_or_default(account);
+        if self.collateral_swept.get_or_default() {
+            claimable.collateral = U256::zero();
+        }
+        claimable
+    }
+
+    /// Clears and returns everything `account` can claim.
+    pub fn take_claimable(&mut self, account: &Address) -> Claimable {
+        let claimable = self.claimable(account);
+        self.claimable.set(account, Claimable::default());
+        self.release_collateral(claimable.collateral);
+        claimable
+    }
+
+    /// Tokens of `account` held by the queue, pending or claimable.
+    pub fn held_tokens(&self, account: &Address, side: Side) -> U256 {
+        let claimable = self.claimable(account);
+        let claimable = match side {
+            Side::Long => claimable.long,
+            Side::Short => claimable.short,
+        };
+        self.pending_tokens(account, side) + claimable
+    }
+
+    /// WCSPR of pending deposits and claimable withdrawals.
+    pub fn held_collateral(&self) -> U256 {
+        self.held_collateral.get_or_default()
+    }
+
+    /// Releases the collateral of the claimable withdrawals and returns it.
+    /// The queue must be empty.
+    pub fn sweep_collateral(&mut self) -> U256 {
+        let collateral = self.held_collateral();
+        self.held_collateral.set(U256::zero());
+        self.collateral_swept.set(true);
+        collateral
+    }
+
+    fn hold_collateral(&mut self, amount: U256) {
+        self.held_collateral.set(self.held_collateral() + amount);
+    }
+
+    fn release_collateral(&mut self, amount: U256) {
+        self.held_collateral.set(self.held_collateral() - amount);
+    }
+}
@@ -32,11 +32,19 @@ use odra::casper_types::U256;
 // And vice versa for the price increase.
 //
 // Then the system updates the total supply of the long or short token.
+//
+// How much liquidity moves is given by the payoff model of the market. The
+// formulas below use the linear model.
 
 pub static ONE_DOLLAR: u64 = 10_000;
 pub static ONE_CENT: u64 = 100;
 pub static ONE_TENTH_CENT: u64 = 10;
 
+/// One whole LONG or SHORT token, which have 9 decimals like WCSPR.
+pub static ONE_TOKEN: u64 = 1_000_000_000;
+/// 100% expressed in basis points.
+pub static BASIS_POINTS: u64 = 10_000;
+
 #[odra::odra_type]
 pub struct MarketState {
     pub long_total_supply: U256,
@@ -53,17 +61,22 @@ impl MarketState {
             short_total_supply: U256::zero(),
             long_liquidity: U256::zero(),
             short_liquidity: U256::zero(),
-            price: price,
+            price,
         }
     }
 
-    pub fn on_price_change(&mut self, new_price: U256) {
+    /// Rebalances the liquidity following `model` and returns the
+    /// shortfall: the part of the move the losing side could not pay
+    /// because it was wiped out.
+    pub fn on_price_change(&mut self, model: &impl PayoffModel, new_price: U256) -> U256 {
         if new_price > self.price {
-            self.on_price_goes_up(new_price);
+            self.on_price_goes_up(model, new_price)
         } else if new_price < self.price {
-            self.on_price_goes_down(new_price);
+            self.on_price_goes_down(model, new_price)
+        } else {
+            // Do nothing when price is the same.
+            U256::zero()
         }
-        // Do nothing when price is the same.
     }
 
     // ## Price goes up
@@ -75,28 +88,70 @@ impl MarketState {
     // $\Delta L_{Short}(i + 1) = L_{Short}(i) \times min(1, \frac{P(i+1)}{P(i)} - 1)$
     // $L_{Short}(i+1) = L_{Short}(i) - \Delta L_{Short}$
     // $L_{Long}(i+1) = L_{Long}(i) + \Delta L_{Short}$
-    pub fn on_price_goes_up(&mut self, new_price: U256) {
-        let delta = self.short_liquidity * new_price / self.price - self.short_liquidity;
-        let delta = self.short_liquidity.min(delta);
+    pub fn on_price_goes_up(&mut self, model: &impl PayoffModel, new_price: U256) -> U256 {
+        let full_delta = model.owed(
+            self.short_liquidity,
+            self.long_liquidity,
+            self.price,
+            new_price,
+        );
+        let delta = self.short_liquidity.min(full_delta);
 
         self.short_liquidity -= delta;
         self.long_liquidity += delta;
         self.price = new_price;
+        full_delta - delta
     }
 
     // ## Price goes down
     //
     // Long position is losing liquidity and it is transferred to the short position.
+    // The adjustment is capped to the total available liquidity the same way.
     //
-    // $\Delta L_{Long}(i + 1) = L_{Long}(i) \times min(1, 1 - \frac{P(i)}{P(i+1)})$
+    // $\Delta L_{Long}(i + 1) = L_{Long}(i) \times min(1, \frac{P(i)}{P(i+1)} - 1)$
     // $L_{Short}(i+1) = L_{Short}(i) + \Delta L_{Long}$
     // $L_{Long}(i+1) = L_{Long}(i) - \Delta L_{Long}$
-    pub fn on_price_goes_down(&mut self, new_price: U256) {
-        let delta = self.long_liquidity * self.price / new_price - self.long_liquidity;
+    pub fn on_price_goes_down(&mut self, model: &impl PayoffModel, new_price: U256) -> U256 {
+        let full_delta = model.owed(
+            self.long_liquidity,
+            self.short_liquidity,
+            self.price,
+            new_price,
+        );
+        let delta = self.long_liquidity.min(full_delta);
 
         self.long_liquidity -= delta;
         self.short_liquidity += delta;
         self.price = new_price;
+        full_delta - delta
+    }
+
+    /// Side gaining liquidity when the price moves to `new_price`.
+    pub fn winning_side(&self, new_price: U256) -> Side {
+        if new_price >= self.price {
+            Side::Long
+        } else {
+            Side::Short
+        }
+    }
+
+    /// Adds liquidity that mints no tokens, like insurance payouts and
+    /// early-exit fees, to the side.
+    pub fn add_liquidity(&mut self, side: Side, amount: U256) {
+        match side {
+            Side::Long => self.long_liquidity += amount,
+            Side::Short => self.short_liquidity += amount,
+        }
+    }
+
+    /// Whether the side's tokens lost all their liquidity. Such a side
+    /// can't take deposits, as the new tokens can't be priced.
+    pub fn is_wiped_out(&self, side: Side) -> bool {
+        let (liquidity, token_supply) = match side {
+            Side::Long => (self.long_liquidity, self.long_total_supply),
+            Side::Short => (self.short_liquidity, self.short_total_supply),
+        };
+        liquidity.is_zero() && !token_supply.is_zero()
     }
 
     // ## Token deposit
@@ -167,13 +222,203 @@ impl MarketState {
 
         delta_liquidity
     }
+
+    // ## Staking rewards
+    //
+    // Rewards $R$ earned by the pooled collateral are split between the long
+    // and short positions proportionally to their liquidity. Token supplies
+    // do not change, so both tokens gain value.
+    //
+    // $\Delta L_{Long} = R \times \frac{L_{Long}(i)}{L_{Long}(i) + L_{Short}(i)}$
+    // $L_{Long}(i+1) = L_{Long}(i) + \Delta L_{Long}$
+    // $L_{Short}(i+1) = L_{Short}(i) + R - \Delta L_{Long}$
+    pub fn on_rewards(&mut self, amount: U256) {
+        let total_liquidity = self.total_liquidity();
+        let long_delta = amount * self.long_liquidity / total_liquidity;
+
+        self.long_liquidity += long_delta;
+        self.short_liquidity += amount - long_delta;
+    }
+
+    pub fn total_liquidity(&self) -> U256 {
+        self.long_liquidity + self.short_liquidity
+    }
+
+    // ## Share price
+    //
+    // The amount of the asset backing one whole token of the side. The first
+    // deposit mints tokens one to one, so an empty side is priced at one.
+    //
+    // $SharePrice = \frac{L}{T}$
+    pub fn share_price(&self, side: Side) -> U256 {
+        let (liquidity, supply) = self.liquidity_and_supply(side);
+        if supply.is_zero() {
+            return U256::from(ONE_TOKEN);
+        }
+        liquidity * U256::from(ONE_TOKEN) / supply
+    }
+
+    /// Returns the share of the side owned by `tokens`, in basis points.
+    pub fn pool_share(&self, side: Side, tokens: U256) -> U256 {
+        let (_, supply) = self.liquidity_and_supply(side);
+        if supply.is_zero() {
+            return U256::zero();
+        }
+        tokens * U256::from(BASIS_POINTS) / supply
+    }
+
+    pub fn liquidity(&self, side: Side) -> U256 {
+        self.liquidity_and_supply(side).0
+    }
+
+    pub fn token_supply(&self, side: Side) -> U256 {
+        self.liquidity_and_supply(side).1
+    }
+
+    fn liquidity_and_supply(&self, side: Side) -> (U256, U256) {
+        match side {
+            Side::Long => (self.long_liquidity, self.long_total_supply),
+            Side::Short => (self.short_liquidity, self.short_total_supply),
+        }
+    }
+}
+
+/// Liquidity moved between the sides on a price change.
+pub trait PayoffModel {
+    /// Returns the liquidity the losing side owes the winning side when the
+    /// price moves from `old_price` to `new_price`. `MarketState` pays at
+    /// most the losing liquidity and reports the rest as the shortfall.
+    fn owed(&self, losing: U256, winning: U256, old_price: U256, new_price: U256) -> U256;
+}
+
+// ## Linear payoff
+//
+// The losing side pays in proportion to the relative price move, measured
+// against the lower of the two prices.
+//
+// $Owed = L_{Losing} \times (\frac{max(P(i), P(i+1))}{min(P(i), P(i+1))} - 1)$
+pub struct Linear;
+
+impl PayoffModel for Linear {
+    fn owed(&self, losing: U256, _winning: U256, old_price: U256, new_price: U256) -> U256 {
+        let (high, low) = high_and_low(old_price, new_price);
+        losing * high / low - losing
+    }
+}
+
+// ## Leveraged linear payoff
+//
+// $Owed = Leverage \times Owed_{Linear}$
+pub struct LeveragedLinear {
+    pub leverage: u64,
+}
+
+impl PayoffModel for LeveragedLinear {
+    fn owed(&self, losing: U256, winning: U256, old_price: U256, new_price: U256) -> U256 {
+        Linear.owed(losing, winning, old_price, new_price) * U256::from(self.leverage)
+    }
+}
+
+// ## Capped payoff
+//
+// The linear payoff, limited to bands of the winning and the losing
+// liquidity, in basis points. A loss band up to 100% never leaves a
+// shortfall.
+//
+// $Owed = min(Owed_{Linear}, L_{Winning} \times MaxGain, L_{Losing} \times MaxLoss)$
+pub struct Capped {
+    pub max_gain: u64,
+    pub max_loss: u64,
 }
-#[derive(Copy, Clone, Debug, PartialEq)]
+
+impl PayoffModel for Capped {
+    fn owed(&self, losing: U256, winning: U256, old_price: U256, new_price: U256) -> U256 {
+        let bps = U256::from(BASIS_POINTS);
+        Linear
+            .owed(losing, winning, old_price, new_price)
+            .min(winning * U256::from(self.max_gain) / bps)
+            .min(losing * U256::from(self.max_loss) / bps)
+    }
+}
+
+// ## Power payoff
+//
+// The losing side pays in proportion to the squared price move.
+//
+// $Owed = L_{Losing} \times ((\frac{max(P(i), P(i+1))}{min(P(i), P(i+1))})^2 - 1)$
+pub struct Power;
+
+impl PayoffModel for Power {
+    fn owed(&self, losing: U256, _winning: U256, old_price: U256, new_price: U256) -> U256 {
+        let (high, low) = high_and_low(old_price, new_price);
+        losing * high * high / (low * low) - losing
+    }
+}
+
+fn high_and_low(a: U256, b: U256) -> (U256, U256) {
+    (a.max(b), a.min(b))
+}
+
+/// Payoff model a market is created with.
+#[odra::odra_type]
+#[derive(Copy, Default)]
+pub enum Payoff {
+    #[default]
+    Linear,
+    /// Linear payoff multiplied by `leverage`.
+    LeveragedLinear { leverage: u64 },
+    /// Linear payoff limited to `max_gain` basis points of the winning
+    /// liquidity and `max_loss` basis points of the losing liquidity.
+    Capped { max_gain: u64, max_loss: u64 },
+    /// Payoff of the squared price move.
+    Power,
+}
+
+impl Payoff {
+    /// Leverage must be at least one and the bands must be positive, with
+    /// the loss band at most 100%.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Payoff::Linear | Payoff::Power => true,
+            Payoff::LeveragedLinear { leverage } => leverage >= 1,
+            Payoff::Capped { max_gain, max_loss } => {
+                max_gain > 0 && max_loss > 0 && max_loss <= BASIS_POINTS
+            }
+        }
+    }
+}
+
+impl PayoffModel for Payoff {
+    fn owed(&self, losing: U256, winning: U256, old_price: U256, new_price: U256) -> U256 {
+        match *self {
+            Payoff::Linear => Linear.owed(losing, winning, old_price, new_price),
+            Payoff::LeveragedLinear { leverage } => {
+                LeveragedLinear { leverage }.owed(losing, winning, old_price, new_price)
+            }
+            Payoff::Capped { max_gain, max_loss } => Capped { max_gain, max_loss }.owed(
+                losing, winning, old_price, new_price,
+            ),
+            Payoff::Power => Power.owed(losing, winning, old_price, new_price),
+        }
+    }
+}
+
+#[odra::odra_type]
+#[derive(Copy)]
 pub enum Side {
     Long,
     Short,
 }
 
+impl Side {
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Long => Side::Short,
+            Side::Short => Side::Long,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,7 +443,7 @@ mod tests {
             short_liquidity: U256::from(100),
             price: U256::from(ONE_CENT),
         };
-        state.on_price_goes_up(U256::from(3 * ONE_CENT));
+        let shortfall = state.on_price_goes_up(&Linear, U256::from(3 * ONE_CENT));
 
         let expected = MarketState {
             long_total_supply: U256::zero(),
@@ -208,6 +453,8 @@ mod tests {
             price: U256::from(3 * ONE_CENT),
         };
         assert_eq!(state, expected);
+        // The short side could only pay 100 of the 200 CSPR.
+        assert_eq!(shortfall, U256::from(100));
     }
 
     // ## Example 2: Price goes up by less then 100%
@@ -231,7 +478,7 @@ mod tests {
             short_liquidity: U256::from(100),
             price: U256::from(ONE_CENT),
         };
-        state.on_price_goes_up(U256::from(14 * ONE_TENTH_CENT));
+        state.on_price_goes_up(&Linear, U256::from(14 * ONE_TENTH_CENT));
 
         let expected = MarketState {
             long_total_supply: U256::zero(),
@@ -243,6 +490,43 @@ mod tests {
         assert_eq!(state, expected);
     }
 
+    // ## Example 3: Price goes down by more then 50%
+    //
+    // Given:
+    // $P(i) = 0.01 \text{USD/CSPR}$
+    // $P(i+1) = 0.004 \text{ USD/CSPR}$
+    // $L_{Short}(i) = 100 \text { CSPR}$
+    // $L_{Long}(i) = 200 \text { CSPR}$
+    //
+    // Then:
+    // $\Delta L_{Long}(i+1) = 200 \times min(1, \frac{0.01}{0.004} - 1) = 200 \times 1 = 200 \text{ CSPR}$
+    // $L_{Short}(i+1) = 100 + 200 = 300 \text{ CSPR}$
+    // $L_{Long}(i+1) = 200 - 200 = 0 \text{ CSPR}$
+    //
+    // The long side should have paid $200 \times 1.5 = 300 \text{ CSPR}$, so
+    // the shortfall is $100 \text{ CSPR}$.
+    #[test]
+    fn example_3_when_price_goes_down_by_more_then_50_percent() {
+        let mut state = MarketState {
+            long_total_supply: U256::zero(),
+            short_total_supply: U256::zero(),
+            long_liquidity: U256::from(200),
+            short_liquidity: U256::from(100),
+            price: U256::from(ONE_CENT),
+        };
+        let shortfall = state.on_price_change(&Linear, U256::from(4 * ONE_TENTH_CENT));
+
+        let expected = MarketState {
+            long_total_supply: U256::zero(),
+            short_total_supply: U256::zero(),
+            long_liquidity: U256::zero(),
+            short_liquidity: U256::from(300),
+            price: U256::from(4 * ONE_TENTH_CENT),
+        };
+        assert_eq!(state, expected);
+        assert_eq!(shortfall, U256::from(100));
+    }
+
     // ## Example 4: Liquidity deposit
     //
     // Given:
@@ -327,4 +611,143 @@ mod tests {
         };
         assert_eq!(state, expected);
     }
+
+    // ## Example 7: Staking rewards
+    //
+    // Given:
+    // $L_{Long}(i) = 300 \text { CSPR}$
+    // $L_{Short}(i) = 100 \text { CSPR}$
+    // $R = 8 \text { CSPR}$
+    //
+    // Then:
+    // $\Delta L_{Long} = 8 \times \frac{300}{300 + 100} = 6 \text { CSPR}$
+    // $L_{Long}(i+1) = 300 + 6 = 306 \text { CSPR}$
+    // $L_{Short}(i+1) = 100 + 8 - 6 = 102 \text { CSPR}$
+    #[test]
+    fn example_7_staking_rewards() {
+        let mut state = MarketState {
+            long_total_supply: U256::from(300),
+            short_total_supply: U256::from(100),
+            long_liquidity: U256::from(300),
+            short_liquidity: U256::from(100),
+            price: U256::from(ONE_CENT),
+        };
+        state.on_rewards(U256::from(8));
+
+        let expected = MarketState {
+            long_total_supply: U256::from(300),
+            short_total_supply: U256::from(100),
+            long_liquidity: U256::from(306),
+            short_liquidity: U256::from(102),
+            price: U256::from(ONE_CENT),
+        };
+        assert_eq!(state, expected);
+    }
+
+    fn state_with_price_goes_up(model: &impl PayoffModel, new_price: u64) -> (MarketState, U256) {
+        let mut state = MarketState {
+            long_total_supply: U256::zero(),
+            short_total_supply: U256::zero(),
+            long_liquidity: U256::from(200),
+            short_liquidity: U256::from(100),
+            price: U256::from(ONE_CENT),
+        };
+        let shortfall = state.on_price_change(model, U256::from(new_price));
+        (state, shortfall)
+    }
+
+    // ## Example 8: Leveraged linear payoff
+    //
+    // With $Leverage = 2$, a price going up from 0.01 to 0.014 USD/CSPR
+    // moves $2 \times 40 = 80 \text{ CSPR}$ from the short position.
+    #[test]
+    fn example_8_leveraged_linear_payoff() {
+        let model = LeveragedLinear { leverage: 2 };
+        let (state, shortfall) = state_with_price_goes_up(&model, 14 * ONE_TENTH_CENT);
+        assert_eq!(state.short_liquidity, U256::from(20));
+        assert_eq!(state.long_liquidity, U256::from(280));
+        assert!(shortfall.is_zero());
+
+        // Going up by 60% takes all the short liquidity and leaves 20 CSPR
+        // unpaid.
+        let (state, shortfall) = state_with_price_goes_up(&model, 16 * ONE_TENTH_CENT);
+        assert_eq!(state.short_liquidity, U256::zero());
+        assert_eq!(shortfall, U256::from(20));
+    }
+
+    // ## Example 9: Capped payoff
+    //
+    // The linear move of 40 CSPR is capped at 10% of the long liquidity,
+    // $200 \times 0.1 = 20 \text{ CSPR}$, and at 30% of the short
+    // liquidity, $100 \times 0.3 = 30 \text{ CSPR}$.
+    #[test]
+    fn example_9_capped_payoff() {
+        let model = Capped {
+            max_gain: 1_000,
+            max_loss: 3_000,
+        };
+        let (state, _) = state_with_price_goes_up(&model, 14 * ONE_TENTH_CENT);
+        assert_eq!(state.short_liquidity, U256::from(80));
+        assert_eq!(state.long_liquidity, U256::from(220));
+
+        // A full loss band never leaves a shortfall.
+        let model = Capped {
+            max_gain: BASIS_POINTS,
+            max_loss: BASIS_POINTS,
+        };
+        let (state, shortfall) = state_with_price_goes_up(&model, 3 * ONE_CENT);
+        assert_eq!(state.short_liquidity, U256::zero());
+        assert!(shortfall.is_zero());
+    }
+
+    // ## Example 10: Power payoff
+    //
+    // $Owed = 100 \times (1.4^2 - 1) = 96 \text{ CSPR}$
+    #[test]
+    fn example_10_power_payoff() {
+        let (state, _) = state_with_price_goes_up(&Power, 14 * ONE_TENTH_CENT);
+        assert_eq!(state.short_liquidity, U256::from(4));
+        assert_eq!(state.long_liquidity, U256::from(296));
+    }
+
+    #[test]
+    fn payoff_enum_matches_the_models() {
+        let (linear, _) = state_with_price_goes_up(&Linear, 14 * ONE_TENTH_CENT);
+        let (payoff, _) = state_with_price_goes_up(&Payoff::Linear, 14 * ONE_TENTH_CENT);
+        assert_eq!(linear, payoff);
+        let (power, _) = state_with_price_goes_up(&Power, 14 * ONE_TENTH_CENT);
+        let (payoff, _) = state_with_price_goes_up(&Payoff::Power, 14 * ONE_TENTH_CENT);
+        assert_eq!(power, payoff);
+
+        assert!(!Payoff::LeveragedLinear { leverage: 0 }.is_valid());
+        assert!(!Payoff::Capped {
+            max_gain: 100,
+            max_loss: BASIS_POINTS + 1
+        }
+        .is_valid());
+        assert!(Payoff::Capped {
+            max_gain: 100,
+            max_loss: 100
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn share_price_and_pool_share() {
+        let state = MarketState {
+            long_total_supply: U256::from(4 * ONE_TOKEN),
+            short_total_supply: U256::zero(),
+            long_liquidity: U256::from(6 * ONE_TOKEN),
+            short_liquidity: U256::zero(),
+            price: U256::from(ONE_CENT),
+        };
+
+        assert_eq!(state.share_price(Side::Long), U256::from(ONE_TOKEN * 3 / 2));
+        assert_eq!(state.share_price(Side::Short), U256::from(ONE_TOKEN));
+        assert_eq!(
+            state.pool_share(Side::Long, U256::from(ONE_TOKEN)),
+            U256::from(2_500)
+        );
+        assert_eq!(state.pool_share(Side::Short, U256::from(ONE_TOKEN)), U256::zero());
+    }
 }
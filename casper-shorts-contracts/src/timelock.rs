@@ -0,0 +1,168 @@
+//! Two-step configuration changes.
+//!
+//! After the first config is set, a new config has to be proposed and can
+//! be executed only once the delay has passed. That leaves time to notice
+//! and cancel a change made with a compromised owner key.
+use odra::{module::Module, prelude::*};
+
+use crate::config::Config;
+
+/// Two days, in milliseconds of the block time.
+pub static DEFAULT_CONFIG_DELAY: u64 = 172_800_000;
+
+/// Config change waiting for its ETA.
+#[odra::odra_type]
+pub struct PendingConfig {
+    pub config: Config,
+    /// Block time from which the change can be executed.
+    pub eta: u64,
+}
+
+impl PendingConfig {
+    pub fn is_ready(&self, block_time: u64) -> bool {
+        block_time >= self.eta
+    }
+}
+
+// Event fields must have a concrete CLType, so the config addresses are
+// listed one by one.
+#[odra::event]
+pub struct ConfigChangeProposed {
+    pub wcspr_token: Address,
+    pub short_token: Address,
+    pub long_token: Address,
+    pub market: Address,
+    pub fee_collector: Address,
+    pub eta: u64,
+}
+
+#[odra::event]
+pub struct ConfigChangeExecuted {
+    pub wcspr_token: Address,
+    pub short_token: Address,
+    pub long_token: Address,
+    pub market: Address,
+    pub fee_collector: Address,
+}
+
+impl From<&Config> for ConfigChangeExecuted {
+    fn from(config: &Config) -> Self {
+        Self {
+            wcspr_token: config.wcspr_token,
+            short_token: config.short_token,
+            long_token: config.long_token,
+            market: config.market,
+            fee_collector: config.fee_collector,
+        }
+    }
+}
+
+#[odra::event]
+pub struct ConfigChangeCancelled {
+    pub wcspr_token: Address,
+    pub short_token: Address,
+    pub long_token: Address,
+    pub market: Address,
+    pub fee_collector: Address,
+}
+
+impl From<&Config> for ConfigChangeCancelled {
+    fn from(config: &Config) -> Self {
+        Self {
+            wcspr_token: config.wcspr_token,
+            short_token: config.short_token,
+            long_token: config.long_token,
+            market: config.market,
+            fee_collector: config.fee_collector,
+        }
+    }
+}
+
+#[odra::event]
+pub struct ConfigDelayChanged {
+    pub delay: u64,
+}
+
+#[odra::module(events = [
+    ConfigChangeProposed,
+    ConfigChangeExecuted,
+    ConfigChangeCancelled,
+    ConfigDelayChanged
+])]
+pub struct ConfigTimelock {
+    delay: Var<u64>,
+    pending: Var<Option<PendingConfig>>,
+}
+
+impl ConfigTimelock {
+    pub fn delay(&self) -> u64 {
+        self.delay.get().unwrap_or(DEFAULT_CONFIG_DELAY)
+    }
+
+    /// Sets the delay. It can only be raised, as lowering it would let a
+    /// compromised key skip it.
+    pub fn set_delay(&mut self, delay: u64) {
+        if delay < self.delay() {
+            self.env().revert(TimelockError::ConfigDelayTooShort);
+        }
+        self.delay.set(delay);
+        self.env().emit_event(ConfigDelayChanged { delay });
+    }
+
+    pub fn pending(&self) -> Option<PendingConfig> {
+        self.pending.get().flatten()
+    }
+
+    /// Stores `config` until the delay passes and returns its ETA.
+    pub fn propose(&mut self, config: Config) -> u64 {
+        if self.pending().is_some() {
+            self.env().revert(TimelockError::ConfigChangePending);
+        }
+        let eta = self.env().get_block_time() + self.delay();
+        self.env().emit_event(ConfigChangeProposed {
+            wcspr_token: config.wcspr_token,
+            short_token: config.short_token,
+            long_token: config.long_token,
+            market: config.market,
+            fee_collector: config.fee_collector,
+            eta,
+        });
+        self.pending.set(Some(PendingConfig { config, eta }));
+        eta
+    }
+
+    /// Removes the pending change and returns its config, reverting if its
+    /// ETA hasn't passed yet.
+    pub fn take_ready(&mut self) -> Config {
+        let pending = self.take_pending();
+        if !pending.is_ready(self.env().get_block_time()) {
+            self.env().revert(TimelockError::ConfigChangeNotReady);
+        }
+        self.env()
+            .emit_event(ConfigChangeExecuted::from(&pending.config));
+        pending.config
+    }
+
+    pub fn cancel(&mut self) {
+        let pending = self.take_pending();
+        self.env()
+            .emit_event(ConfigChangeCancelled::from(&pending.config));
+    }
+
+    fn take_pending(&mut self) -> PendingConfig {
+        let pending = self
+            .pending()
+            .unwrap_or_revert_with(&self.env(), TimelockError::NoPendingConfig);
+        self.pending.set(None);
+        pending
+    }
+}
+
+#[odra::odra_error]
+pub enum TimelockError {
+    ConfigAlreadySet = 11001,
+    ConfigChangePending = 11002,
+    NoPendingConfig = 11003,
+    ConfigChangeNotReady = 11004,
+    ConfigDelayTooShort = 11005,
+}
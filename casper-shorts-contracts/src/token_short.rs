@@ -1,7 +1,11 @@
-use odra::{casper_types::U256, prelude::*, Address, SubModule};
+use odra::{casper_types::U256, prelude::*};
 use odra_modules::{access::Ownable, cep18::{errors::Error as Cep18Error, utils::Cep18Modality}, cep18_token::Cep18};
 
-use crate::config::{Config, ConfigModule};
+use crate::{
+    config::{Config, ConfigModule},
+    timelock::{ConfigTimelock, PendingConfig, TimelockError},
+    version::StateVersion,
+};
 
 /// A module definition. Each module struct consists of Vars and Mappings
 /// or/and other modules.
@@ -11,6 +15,10 @@ pub struct TokenShort {
     token: SubModule<Cep18>,
     cfg: SubModule<ConfigModule>,
     ownable: SubModule<Ownable>,
+    timelock: SubModule<ConfigTimelock>,
+    /// Block time of the last deposit of each account.
+    last_deposit: Mapping<Address, u64>,
+    version: SubModule<StateVersion>,
 }
 
 /// Module implementation.
@@ -31,22 +39,79 @@ impl TokenShort {
             Some(Cep18Modality::MintAndBurn),
         );
         self.ownable.init();
+        self.version.init();
     }
 
+    /// Sets the first config. Later changes go through `propose_config`.
     pub fn set_config(&mut self, cfg: Config) {
         self.ownable.assert_owner(&self.env().caller());
+        if self.cfg.try_get().is_some() {
+            self.env().revert(TimelockError::ConfigAlreadySet);
+        }
+        self.cfg.set(cfg);
+    }
+
+    /// Proposes a new config, executable once the config delay has passed.
+    /// Returns the ETA of the change.
+    pub fn propose_config(&mut self, cfg: Config) -> u64 {
+        self.ownable.assert_owner(&self.env().caller());
+        self.timelock.propose(cfg)
+    }
+
+    /// Applies the proposed config after its ETA. Anyone can call it.
+    pub fn execute_config(&mut self) {
+        let cfg = self.timelock.take_ready();
         self.cfg.set(cfg);
     }
 
+    pub fn cancel_config(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.timelock.cancel();
+    }
+
+    pub fn get_pending_config(&self) -> Option<PendingConfig> {
+        self.timelock.pending()
+    }
+
+    /// Raises the delay of config changes.
+    pub fn set_config_delay(&mut self, delay: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.timelock.set_delay(delay);
+    }
+
+    pub fn get_config_delay(&self) -> u64 {
+        self.timelock.delay()
+    }
+
+    /// Brings the stored state to the current layout after new code is
+    /// installed.
+    pub fn migrate(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        let from = self.version.start_migration();
+        self.version.finish_migration(from);
+    }
+
+    pub fn get_state_version(&self) -> u32 {
+        self.version.get()
+    }
+
+    pub fn get_config(&self) -> Option<Config> {
+        self.cfg.try_get()
+    }
+
     pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
         let sender = self.env().caller();
         let pack = self.cfg.get();
-        if pack.is_wcspr_token(&recipient) {
+        if pack.is_wcspr_token(recipient) {
             self.cfg
                 .market()
                 .withdraw_short_from(&sender, *amount);
         } else {
-            self.token.raw_transfer(&sender, &recipient, &amount);
+            self.cfg
+                .market()
+                .on_token_transfer(&sender, recipient, *amount);
+            self.pass_last_deposit(&sender, recipient);
+            self.token.raw_transfer(&sender, recipient, amount);
         }
     }
 
@@ -56,10 +121,26 @@ impl TokenShort {
         if pack.is_market(&sender) {
             self.token.raw_transfer(owner, recipient, amount);
         } else {
+            self.cfg
+                .market()
+                .on_token_transfer(owner, recipient, *amount);
+            self.pass_last_deposit(owner, recipient);
             self.token.transfer_from(owner, recipient, amount);
         }
     }
 
+    /// Mints new tokens and assigns them to the given address.
+    /// The block time is recorded as the owner's last deposit.
+    pub fn mint(&mut self, owner: &Address, amount: &U256) {
+        self.token.mint(owner, amount);
+        self.last_deposit.set(owner, self.env().get_block_time());
+    }
+
+    /// Returns the block time of the last deposit of the given address.
+    pub fn last_deposit_of(&self, owner: &Address) -> u64 {
+        self.last_deposit.get_or_default(owner)
+    }
+
     /// Burns the given amount of tokens from the given address.
     pub fn burn(&mut self, owner: &Address, amount: &U256) {
         // self.assert_burn_and_mint_enabled();
@@ -117,13 +198,27 @@ impl TokenShort {
 
             /// Increases the allowance of the spender by the given amount.
             fn increase_allowance(&mut self, spender: &Address, inc_by: &U256);
+        }
+
+        to self.ownable {
+            /// Hands the owner rights over, e.g. to a multisig.
+            fn transfer_ownership(&mut self, new_owner: &Address);
 
-            /// Mints new tokens and assigns them to the given address.
-            fn mint(&mut self, owner: &Address, amount: &U256);
+            /// Returns the owner of the contract.
+            fn get_owner(&self) -> Address;
         }
     }
 }
 
+impl TokenShort {
+    /// Tokens received from another account carry the later of both last
+    /// deposits, so a transfer can't bypass the holding period.
+    fn pass_last_deposit(&mut self, sender: &Address, recipient: &Address) {
+        let last_deposit = self.last_deposit_of(sender).max(self.last_deposit_of(recipient));
+        self.last_deposit.set(recipient, last_deposit);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,6 +237,6 @@ mod tests {
             decimals: 10,
             initial_supply: U256::from(1_000_000_000_000u64),
         };
-        assert!(TokenShortHostRef::try_deploy(&env, init_args).is_ok());
+        assert!(TokenShort::try_deploy(&env, init_args).is_ok());
     }
 }
@@ -1,7 +1,14 @@
-use odra::{casper_types::U256, prelude::*, Address, SubModule};
+use odra::{casper_types::U256, prelude::*, ContractRef};
 use odra_modules::{access::Ownable, cep18::utils::Cep18Modality, cep18_token::Cep18};
 
-use crate::config::{Config, ConfigModule};
+use crate::{
+    config::{Config, ConfigModule},
+    market::MarketContractRef,
+    routes::{MarketRoutes, RouteError},
+    system::Side,
+    timelock::{ConfigTimelock, PendingConfig, TimelockError},
+    version::StateVersion,
+};
 
 /// A module definition. Each module struct consists of Vars and Mappings
 /// or/and other modules.
@@ -10,6 +17,10 @@ pub struct TokenWCSPR {
     token: SubModule<Cep18>,
     cfg: SubModule<ConfigModule>,
     ownable: SubModule<Ownable>,
+    timelock: SubModule<ConfigTimelock>,
+    version: SubModule<StateVersion>,
+    /// Markets added after the configured one.
+    routes: SubModule<MarketRoutes>,
 }
 
 #[odra::module]
@@ -26,34 +37,120 @@ impl TokenWCSPR {
             Some(Cep18Modality::MintAndBurn),
         );
         self.ownable.init();
+        self.version.init();
     }
 
+    /// Sets the first config. Later changes go through `propose_config`.
     pub fn set_config(&mut self, cfg: Config) {
         self.ownable.assert_owner(&self.env().caller());
+        if self.cfg.try_get().is_some() {
+            self.env().revert(TimelockError::ConfigAlreadySet);
+        }
+        self.cfg.set(cfg);
+    }
+
+    /// Proposes a new config, executable once the config delay has passed.
+    /// Returns the ETA of the change.
+    pub fn propose_config(&mut self, cfg: Config) -> u64 {
+        self.ownable.assert_owner(&self.env().caller());
+        self.timelock.propose(cfg)
+    }
+
+    /// Applies the proposed config after its ETA. Anyone can call it.
+    pub fn execute_config(&mut self) {
+        let cfg = self.timelock.take_ready();
         self.cfg.set(cfg);
     }
 
+    pub fn cancel_config(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.timelock.cancel();
+    }
+
+    pub fn get_pending_config(&self) -> Option<PendingConfig> {
+        self.timelock.pending()
+    }
+
+    /// Raises the delay of config changes.
+    pub fn set_config_delay(&mut self, delay: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.timelock.set_delay(delay);
+    }
+
+    pub fn get_config_delay(&self) -> u64 {
+        self.timelock.delay()
+    }
+
+    /// Proposes routing deposits to the market of `cfg`, e.g. the next
+    /// expiry, on top of the configured one. Returns the ETA of the route.
+    pub fn propose_market(&mut self, cfg: Config) -> u64 {
+        self.ownable.assert_owner(&self.env().caller());
+        if cfg.wcspr_token != self.env().self_address() {
+            self.env().revert(RouteError::NotThisToken);
+        }
+        let delay = self.timelock.delay();
+        self.routes.propose(cfg, delay)
+    }
+
+    /// Adds the proposed route to `market` after its ETA. Anyone can call it.
+    pub fn add_market(&mut self, market: Address) {
+        self.routes.add(&market);
+    }
+
+    pub fn cancel_market(&mut self, market: Address) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.routes.cancel(&market);
+    }
+
+    pub fn get_proposed_market(&self, market: Address) -> Option<PendingConfig> {
+        self.routes.proposed(&market)
+    }
+
+    /// Whether transfers to the LONG and SHORT tokens of `cfg` deposit into
+    /// its market.
+    pub fn routes_to(&self, cfg: Config) -> bool {
+        self.cfg.try_get().as_ref() == Some(&cfg) || self.routes.routes_to(&cfg)
+    }
+
+    /// Brings the stored state to the current layout after new code is
+    /// installed.
+    pub fn migrate(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        let from = self.version.start_migration();
+        self.version.finish_migration(from);
+    }
+
+    pub fn get_state_version(&self) -> u32 {
+        self.version.get()
+    }
+
     pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
         let sender = self.env().caller();
         let pack = self.cfg.get();
-        if pack.is_long_token(&recipient) {
+        if pack.is_long_token(recipient) {
             self.cfg
                 .market()
                 .deposit_long_from(&sender, *amount);
-        } else if pack.is_short_token(&recipient) {
+        } else if pack.is_short_token(recipient) {
             self.cfg
                 .market()
                 .deposit_short_from(&sender, *amount);
+        } else if let Some(route) = self.routes.route(recipient) {
+            let mut market = MarketContractRef::new(self.env(), route.market);
+            match route.side {
+                Side::Long => market.deposit_long_from(&sender, *amount),
+                Side::Short => market.deposit_short_from(&sender, *amount),
+            }
         } else {
             // In other cases, transfer the token.
-            self.token.raw_transfer(&sender, &recipient, &amount);
+            self.token.raw_transfer(&sender, recipient, amount);
         }
     }
 
     pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
         let sender = self.env().caller();
         let pack = self.cfg.get();
-        if pack.is_market(&sender) {
+        if pack.is_market(&sender) || self.routes.is_market(&sender) {
             self.token.raw_transfer(owner, recipient, amount);
         } else {
             self.token.transfer_from(owner, recipient, amount);
@@ -103,10 +200,10 @@ impl TokenWCSPR {
             /// Increases the allowance of the spender by the given amount.
             fn increase_allowance(&mut self, spender: &Address, inc_by: &U256);
 
-            /// Transfers tokens from the caller to the recipient.
+            // Transfers tokens from the caller to the recipient.
             // fn transfer(&mut self, recipient: &Address, amount: &U256);
 
-            /// Transfers tokens from the owner to the recipient using the spender's allowance.
+            // Transfers tokens from the owner to the recipient using the spender's allowance.
             // fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
 
             /// Mints new tokens and assigns them to the given address.
@@ -115,6 +212,14 @@ impl TokenWCSPR {
             /// Burns the given amount of tokens from the given address.
             fn burn(&mut self, owner: &Address, amount: &U256);
         }
+
+        to self.ownable {
+            /// Hands the owner rights over, e.g. to a multisig.
+            fn transfer_ownership(&mut self, new_owner: &Address);
+
+            /// Returns the owner of the contract.
+            fn get_owner(&self) -> Address;
+        }
     }
 }
 
@@ -136,6 +241,6 @@ mod tests {
             decimals: 10,
             initial_supply: U256::from(1_000_000_000_000u64),
         };
-        assert!(TokenWCSPRHostRef::try_deploy(&env, init_args).is_ok());
+        assert!(TokenWCSPR::try_deploy(&env, init_args).is_ok());
     }
 }
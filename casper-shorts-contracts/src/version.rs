@@ -0,0 +1,65 @@
+//! Version of the storage layout of a contract.
+//!
+//! New code installed over an existing contract calls `migrate` once to
+//! bring the stored state to `STATE_VERSION`. Contracts deployed before the
+//! layout was versioned have no version stored and count as version 0.
+//!
+//! Version 0 contracts were installed as locked packages by odra 1.0 and
+//! can't get new code, so migrating from version 0 only records the
+//! version. The client installs the contracts as upgradable packages, and
+//! its `upgrade` command calls `migrate` once new code is added. Later
+//! layouts add their steps to the contracts' `migrate`.
+use odra::{module::Module, prelude::*};
+
+/// Current storage layout.
+pub static STATE_VERSION: u32 = 1;
+
+#[odra::event]
+pub struct StateMigrated {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[odra::module(events = [StateMigrated])]
+pub struct StateVersion {
+    version: Var<u32>,
+}
+
+impl StateVersion {
+    /// Marks a freshly deployed contract as using the current layout.
+    pub fn init(&mut self) {
+        self.version.set(STATE_VERSION);
+    }
+
+    pub fn get(&self) -> u32 {
+        self.version.get_or_default()
+    }
+
+    /// Returns the stored version, reverting if there is nothing to
+    /// migrate. The caller migrates its state from it and then calls
+    /// `finish_migration`.
+    pub fn start_migration(&self) -> u32 {
+        let version = self.get();
+        if version == STATE_VERSION {
+            self.env().revert(VersionError::StateAlreadyCurrent);
+        }
+        if version > STATE_VERSION {
+            self.env().revert(VersionError::UnknownStateVersion);
+        }
+        version
+    }
+
+    pub fn finish_migration(&mut self, from: u32) {
+        self.version.set(STATE_VERSION);
+        self.env().emit_event(StateMigrated {
+            from,
+            to: STATE_VERSION,
+        });
+    }
+}
+
+#[odra::odra_error]
+pub enum VersionError {
+    StateAlreadyCurrent = 13001,
+    UnknownStateVersion = 13002,
+}
@@ -1,2 +1,3 @@
 pub mod params;
+pub mod reentrant_token;
 pub mod world;
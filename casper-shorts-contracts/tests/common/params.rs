@@ -1,12 +1,24 @@
 use std::{fmt::Display, str::FromStr};
 
-use casper_shorts_contracts::system::ONE_DOLLAR;
+use casper_shorts_contracts::{
+    competition::CompetitionError,
+    expiry::ExpiryError,
+    market::MarketError,
+    multisig::MultisigError,
+    registry::RegistryError,
+    routes::RouteError,
+    system::{Side, ONE_DOLLAR},
+    timelock::TimelockError,
+    version::VersionError,
+};
 use cucumber::Parameter;
-use odra::casper_types::U256;
+use odra::{casper_types::U256, prelude::*};
+use odra_modules::{access::errors::Error as AccessError, cep18::errors::Error as Cep18Error};
 
 #[derive(Debug, Parameter, Clone, Copy)]
 #[param(name = "account", regex = ".+")]
 pub enum Account {
+    Admin = 0,
     Alice = 1,
     Bob = 2,
     Charlie = 3,
@@ -22,6 +34,7 @@ impl FromStr for Account {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "Admin" => Ok(Account::Admin),
             "Alice" => Ok(Account::Alice),
             "Bob" => Ok(Account::Bob),
             "Charlie" => Ok(Account::Charlie),
@@ -41,6 +54,7 @@ impl Account {
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Parameter, Clone, Copy)]
 #[param(name = "token_kind", regex = ".+")]
 pub enum TokenKind {
@@ -62,6 +76,16 @@ impl FromStr for TokenKind {
     }
 }
 
+impl TokenKind {
+    pub fn side(&self) -> Side {
+        match self {
+            TokenKind::LONG => Side::Long,
+            TokenKind::SHORT => Side::Short,
+            TokenKind::WCSPR => panic!("WCSPR is not a market side"),
+        }
+    }
+}
+
 #[derive(Debug, Parameter, Clone, Copy)]
 #[param(name = "amount", regex = ".+")]
 pub struct Amount(pub U256);
@@ -123,3 +147,89 @@ impl Price {
         num as f64 / ONE_DOLLAR as f64
     }
 }
+
+#[derive(Debug, Parameter, Clone)]
+#[param(name = "error", regex = "[A-Za-z]+")]
+pub struct ExpectedError(pub OdraError);
+
+impl FromStr for ExpectedError {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error: OdraError = match s {
+            "DeadlineExpired" => MarketError::DeadlineExpired.into(),
+            "InsufficientTokensOut" => MarketError::InsufficientTokensOut.into(),
+            "InsufficientCollateralOut" => MarketError::InsufficientCollateralOut.into(),
+            "NewPriceIsTooOld" => MarketError::NewPriceIsTooOld.into(),
+            "NewPriceIsFromTheFuture" => MarketError::NewPriceIsFromTheFuture.into(),
+            "CallerNotTheOwner" => AccessError::CallerNotTheOwner.into(),
+            "InsufficientBalance" => Cep18Error::InsufficientBalance.into(),
+            "DepositTooSmall" => MarketError::DepositTooSmall.into(),
+            "WithdrawTooSmall" => MarketError::WithdrawTooSmall.into(),
+            "SideLiquidityCapExceeded" => MarketError::SideLiquidityCapExceeded.into(),
+            "AccountShareExceeded" => MarketError::AccountShareExceeded.into(),
+            "ImbalanceExceeded" => MarketError::ImbalanceExceeded.into(),
+            "InvalidImbalanceRatio" => MarketError::InvalidImbalanceRatio.into(),
+            "InvalidRecipient" => MarketError::InvalidRecipient.into(),
+            "FlipFeeTooHigh" => MarketError::FlipFeeTooHigh.into(),
+            "InsuranceFeeShareTooHigh" => MarketError::InsuranceFeeShareTooHigh.into(),
+            "SettlementIsDeferred" => MarketError::SettlementIsDeferred.into(),
+            "NothingToClaim" => MarketError::NothingToClaim.into(),
+            "EarlyExitFeeTooHigh" => MarketError::EarlyExitFeeTooHigh.into(),
+            "MarketAlreadyRegistered" => RegistryError::MarketAlreadyRegistered.into(),
+            "MarketNotConfigured" => RegistryError::MarketNotConfigured.into(),
+            "LongTokenNotConfigured" => RegistryError::LongTokenNotConfigured.into(),
+            "PriceFeedNotKeeper" => RegistryError::PriceFeedNotKeeper.into(),
+            "WcsprNotRouted" => RegistryError::WcsprNotRouted.into(),
+            "RouteNotReady" => RouteError::RouteNotReady.into(),
+            "AlreadyRouted" => RouteError::AlreadyRouted.into(),
+            "ConfigAlreadySet" => TimelockError::ConfigAlreadySet.into(),
+            "ConfigChangePending" => TimelockError::ConfigChangePending.into(),
+            "NoPendingConfig" => TimelockError::NoPendingConfig.into(),
+            "ConfigChangeNotReady" => TimelockError::ConfigChangeNotReady.into(),
+            "ConfigDelayTooShort" => TimelockError::ConfigDelayTooShort.into(),
+            "NotASigner" => MultisigError::NotASigner.into(),
+            "InvalidThreshold" => MultisigError::InvalidThreshold.into(),
+            "DuplicateSigner" => MultisigError::DuplicateSigner.into(),
+            "AlreadyApproved" => MultisigError::AlreadyApproved.into(),
+            "NotApproved" => MultisigError::NotApproved.into(),
+            "ProposalAlreadyExecuted" => MultisigError::ProposalAlreadyExecuted.into(),
+            "NotEnoughApprovals" => MultisigError::NotEnoughApprovals.into(),
+            "CallerNotMultisig" => MultisigError::CallerNotMultisig.into(),
+            "WCSPRTokenContractNotACallerOnLongDeposit" => {
+                MarketError::WCSPRTokenContractNotACallerOnLongDeposit.into()
+            }
+            "WCSPRTokenContractNotACallerOnShortDeposit" => {
+                MarketError::WCSPRTokenContractNotACallerOnShortDeposit.into()
+            }
+            "LongTokenContractNotACallerOnWithdrawal" => {
+                MarketError::LongTokenContractNotACallerOnWithdrawal.into()
+            }
+            "ShortTokenContractNotACallerOnWithdrawal" => {
+                MarketError::ShortTokenContractNotACallerOnWithdrawal.into()
+            }
+            "ReentrantCall" => ExecutionError::ReentrantCall.into(),
+            "MarketExpired" => ExpiryError::MarketExpired.into(),
+            "MarketNotExpired" => ExpiryError::MarketNotExpired.into(),
+            "GracePeriodNotOver" => ExpiryError::GracePeriodNotOver.into(),
+            "StateAlreadyCurrent" => VersionError::StateAlreadyCurrent.into(),
+            "NotOrderOwner" => MarketError::NotOrderOwner.into(),
+            "OrderNotOpen" => MarketError::OrderNotOpen.into(),
+            "OrderTooSmall" => MarketError::OrderTooSmall.into(),
+            "TooManyOpenOrders" => MarketError::TooManyOpenOrders.into(),
+            "KeeperFeeShareTooHigh" => MarketError::KeeperFeeShareTooHigh.into(),
+            "RequestsPending" => MarketError::RequestsPending.into(),
+            "SideWipedOut" => MarketError::SideWipedOut.into(),
+            "TooManyQueuedRequests" => MarketError::TooManyQueuedRequests.into(),
+            "MarketPaused" => MarketError::MarketPaused.into(),
+            "CompetitionEnded" => CompetitionError::CompetitionEnded.into(),
+            "CompetitionNotEnded" => CompetitionError::CompetitionNotEnded.into(),
+            "AlreadyRegistered" => CompetitionError::AlreadyRegistered.into(),
+            "AlreadyFinalized" => CompetitionError::AlreadyFinalized.into(),
+            "CompetitionNotStarted" => CompetitionError::CompetitionNotStarted.into(),
+            "TooManyParticipants" => CompetitionError::TooManyParticipants.into(),
+            _ => return Err(format!("Invalid error: {}", s)),
+        };
+        Ok(Self(error))
+    }
+}
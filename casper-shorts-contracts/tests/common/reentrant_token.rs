@@ -0,0 +1,85 @@
+//! Malicious token that calls back into the Market while the Market calls it.
+use casper_shorts_contracts::market::MarketContractRef;
+use odra::{casper_types::U256, module::Module, prelude::*, ContractRef};
+
+/// Market entry point the token re-enters.
+#[odra::odra_type]
+#[derive(Copy, Default)]
+pub enum Attack {
+    #[default]
+    None,
+    DepositLong,
+    WithdrawLong,
+    DepositLongFrom,
+    OnTokenTransfer,
+}
+
+/// Minimal token with the entry points the Market calls. Balances move
+/// without allowances, so only the re-entry makes a call fail.
+#[odra::module]
+pub struct ReentrantToken {
+    balances: Mapping<Address, U256>,
+    market: Var<Address>,
+    attack: Var<Attack>,
+}
+
+#[odra::module]
+impl ReentrantToken {
+    pub fn arm(&mut self, market: Address, attack: Attack) {
+        self.market.set(market);
+        self.attack.set(attack);
+    }
+
+    pub fn mint(&mut self, owner: &Address, amount: &U256) {
+        self.attack(owner, *amount);
+        self.balances.set(owner, self.balance_of(owner) + *amount);
+    }
+
+    pub fn burn(&mut self, owner: &Address, amount: &U256) {
+        self.attack(owner, *amount);
+        self.balances.set(owner, self.balance_of(owner) - *amount);
+    }
+
+    pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
+        let sender = self.env().caller();
+        self.attack(&sender, *amount);
+        self.move_balance(&sender, recipient, *amount);
+    }
+
+    pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        self.attack(owner, *amount);
+        self.move_balance(owner, recipient, *amount);
+    }
+
+    pub fn balance_of(&self, address: &Address) -> U256 {
+        self.balances.get_or_default(address)
+    }
+
+    pub fn last_deposit_of(&self, owner: &Address) -> u64 {
+        // Named like TokenLong's argument, as the Market passes it by name.
+        // Deposits are never locked.
+        let _ = owner;
+        0
+    }
+}
+
+impl ReentrantToken {
+    fn attack(&self, account: &Address, amount: U256) {
+        let Some(market) = self.market.get() else {
+            return;
+        };
+        let mut market = MarketContractRef::new(self.env(), market);
+        match self.attack.get_or_default() {
+            Attack::None => {}
+            Attack::DepositLong => market.deposit_long(amount),
+            Attack::WithdrawLong => market.withdraw_long(amount),
+            Attack::DepositLongFrom => market.deposit_long_from(account, amount),
+            Attack::OnTokenTransfer => market.on_token_transfer(account, account, amount),
+        }
+    }
+
+    fn move_balance(&mut self, from: &Address, to: &Address, amount: U256) {
+        self.balances.set(from, self.balance_of(from) - amount);
+        self.balances.set(to, self.balance_of(to) + amount);
+    }
+}
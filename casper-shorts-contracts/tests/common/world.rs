@@ -1,24 +1,51 @@
 use std::fmt::{Debug, Formatter};
 
 use casper_shorts_contracts::{
-    config::{self, Config},
-    market::{MarketHostRef, MarketInitArgs},
+    competition::{Competition, CompetitionHostRef, CompetitionInitArgs, Standing},
+    config::Config,
+    insurance::{Payout, PayoutReason},
+    keepers::KeeperConfig,
+    limits::{EarlyExitFee, ImbalanceGuard, MarketLimits},
+    market::{Market, MarketHostRef, MarketInitArgs, Position, RewardsDestination, TradePreview},
+    multisig::{Multisig, MultisigHostRef, MultisigInitArgs},
+    orders::{Order, Trigger},
+    pnl::PnlReport,
     price_data::PriceData,
-    system::{MarketState, ONE_CENT},
-    token_long::{TokenLongHostRef, TokenLongInitArgs},
-    token_short::{TokenShortHostRef, TokenShortInitArgs},
-    token_wcspr::{TokenWCSPRHostRef, TokenWCSPRInitArgs},
+    registry::{MarketRegistry, MarketRegistryHostRef},
+    settlement::{Claimable, PendingRequest},
+    system::{MarketState, Payoff, Side, ONE_CENT},
+    token_long::{TokenLong, TokenLongHostRef, TokenLongInitArgs},
+    token_short::{TokenShort, TokenShortHostRef, TokenShortInitArgs},
+    token_wcspr::{TokenWCSPR, TokenWCSPRHostRef, TokenWCSPRInitArgs},
 };
 use odra::{
-    casper_types::U256,
-    host::{Deployer, HostEnv, HostRef},
-    Address,
+    casper_types::{bytesrepr::ToBytes, RuntimeArgs, U256},
+    host::{Deployer, HostEnv, HostRef, NoArgs},
+    prelude::*,
 };
 
-use super::params::{Account, TokenKind};
+use super::{
+    params::{Account, TokenKind},
+    reentrant_token::{Attack, ReentrantToken, ReentrantTokenHostRef},
+};
 
 const INITIAL_WCSPR_BALANCE: u64 = 1_000_000_000_000u64; // 1000 CSPR
 
+/// Market wired to malicious tokens only.
+pub struct ReentrantMarket {
+    pub market: MarketHostRef,
+    pub wcspr_token: ReentrantTokenHostRef,
+    pub long_token: ReentrantTokenHostRef,
+    pub short_token: ReentrantTokenHostRef,
+}
+
+/// Second market sharing the WCSPR token, like the next expiry.
+pub struct NextMarket {
+    pub market: MarketHostRef,
+    pub long_token: TokenLongHostRef,
+    pub short_token: TokenShortHostRef,
+}
+
 #[derive(cucumber::World)]
 pub struct CasperShortsWorld {
     pub odra_env: HostEnv,
@@ -26,14 +53,28 @@ pub struct CasperShortsWorld {
     pub short_token: TokenShortHostRef,
     pub long_token: TokenLongHostRef,
     pub market: MarketHostRef,
+    pub competition: Option<CompetitionHostRef>,
+    pub registry: Option<MarketRegistryHostRef>,
+    pub multisig: Option<MultisigHostRef>,
+    pub reentrant: Option<ReentrantMarket>,
+    pub next_market: Option<NextMarket>,
+    pub last_error: Option<OdraError>,
 }
 
 impl Default for CasperShortsWorld {
     fn default() -> Self {
+        Self::with_market(None, Payoff::Linear)
+    }
+}
+
+impl CasperShortsWorld {
+    /// Deploys the system with a market using `payoff`, dated if `expiry`
+    /// is given.
+    pub fn with_market(expiry: Option<u64>, payoff: Payoff) -> Self {
         let odra_env = odra_test::env();
         odra_env.advance_block_time(100);
 
-        let mut wcspr_token = TokenWCSPRHostRef::deploy(
+        let mut wcspr_token = TokenWCSPR::deploy(
             &odra_env,
             TokenWCSPRInitArgs {
                 name: "CasperShorts".to_string(),
@@ -43,7 +84,7 @@ impl Default for CasperShortsWorld {
             },
         );
 
-        let mut short_token = TokenShortHostRef::deploy(
+        let mut short_token = TokenShort::deploy(
             &odra_env,
             TokenShortInitArgs {
                 name: "CS_SHORT".to_string(),
@@ -53,7 +94,7 @@ impl Default for CasperShortsWorld {
             },
         );
 
-        let mut long_token = TokenLongHostRef::deploy(
+        let mut long_token = TokenLong::deploy(
             &odra_env,
             TokenLongInitArgs {
                 name: "CS_LONG".to_string(),
@@ -63,22 +104,24 @@ impl Default for CasperShortsWorld {
             },
         );
 
-        let mut market = MarketHostRef::deploy(
+        let mut market = Market::deploy(
             &odra_env,
             MarketInitArgs {
                 last_price: PriceData {
                     price: ONE_CENT.into(),
-                    timestamp: 0u64.into(),
+                    timestamp: 0u64,
                 },
+                expiry,
+                payoff,
             },
         );
 
         // Update addresses.
         let cfg = Config {
-            wcspr_token: wcspr_token.address().clone(),
-            short_token: short_token.address().clone(),
-            long_token: long_token.address().clone(),
-            market: market.address().clone(),
+            wcspr_token: *wcspr_token.address(),
+            short_token: *short_token.address(),
+            long_token: *long_token.address(),
+            market: *market.address(),
             fee_collector: odra_env.get_account(Account::FeeCollector.index()),
         };
 
@@ -88,8 +131,8 @@ impl Default for CasperShortsWorld {
         wcspr_token.set_config(cfg.clone());
 
         // Make market minter of LONG and SHORT tokens.
-        short_token.change_security(vec![], vec![market.address().clone()], vec![]);
-        long_token.change_security(vec![], vec![market.address().clone()], vec![]);
+        short_token.change_security(vec![], vec![*market.address()], vec![]);
+        long_token.change_security(vec![], vec![*market.address()], vec![]);
 
         let mut world = CasperShortsWorld {
             wcspr_token,
@@ -97,6 +140,12 @@ impl Default for CasperShortsWorld {
             short_token,
             long_token,
             market,
+            competition: None,
+            registry: None,
+            multisig: None,
+            reentrant: None,
+            next_market: None,
+            last_error: None,
         };
         world.mint(
             TokenKind::WCSPR,
@@ -122,10 +171,10 @@ impl Debug for CasperShortsWorld {
 impl CasperShortsWorld {
     pub fn address(&self, account: Account) -> Address {
         match account {
-            Account::MarketContract => self.market.address().clone(),
-            Account::LongContract => self.long_token.address().clone(),
-            Account::ShortContract => self.short_token.address().clone(),
-            Account::WCSPRContract => self.wcspr_token.address().clone(),
+            Account::MarketContract => *self.market.address(),
+            Account::LongContract => *self.long_token.address(),
+            Account::ShortContract => *self.short_token.address(),
+            Account::WCSPRContract => *self.wcspr_token.address(),
             _ => self.odra_env.get_account(account.index()),
         }
     }
@@ -176,13 +225,196 @@ impl CasperShortsWorld {
         self.market.withdraw_short(amount);
     }
 
+    pub fn go_long_for(&mut self, payer: Account, recipient: Account, amount: U256) {
+        self.odra_env.set_caller(self.address(payer));
+        self.wcspr_token.approve(self.market.address(), &amount);
+        let result = self
+            .market
+            .try_deposit_long_for(&self.address(recipient), amount);
+        self.record(result);
+    }
+
+    pub fn go_short_for(&mut self, payer: Account, recipient: Account, amount: U256) {
+        self.odra_env.set_caller(self.address(payer));
+        self.wcspr_token.approve(self.market.address(), &amount);
+        let result = self
+            .market
+            .try_deposit_short_for(&self.address(recipient), amount);
+        self.record(result);
+    }
+
+    pub fn withdraw_to(&mut self, account: Account, side: Side, amount: U256, recipient: Account) {
+        let recipient = self.address(recipient);
+        self.odra_env.set_caller(self.address(account));
+        let result = match side {
+            Side::Long => self.market.try_withdraw_long_to(&recipient, amount),
+            Side::Short => self.market.try_withdraw_short_to(&recipient, amount),
+        };
+        self.record(result);
+    }
+
+    /// Calls a token hook of the market directly, spending `owner`'s funds.
+    pub fn call_hook(&mut self, caller: Account, hook: &str, owner: Account, amount: U256) {
+        let owner = self.address(owner);
+        self.odra_env.set_caller(self.address(caller));
+        let result = match hook {
+            "deposit_long_from" => self.market.try_deposit_long_from(&owner, amount),
+            "deposit_short_from" => self.market.try_deposit_short_from(&owner, amount),
+            "withdraw_long_from" => self.market.try_withdraw_long_from(&owner, amount),
+            "withdraw_short_from" => self.market.try_withdraw_short_from(&owner, amount),
+            _ => panic!("Unknown hook: {}", hook),
+        };
+        self.record(result);
+    }
+
+    pub fn go_long_with_slippage(
+        &mut self,
+        account: Account,
+        amount: U256,
+        min_tokens_out: U256,
+        deadline: u64,
+    ) {
+        let address = self.address(account);
+        self.odra_env.set_caller(address);
+        self.wcspr_token.approve(self.market.address(), &amount);
+        let result = self
+            .market
+            .try_deposit_long_with_slippage(amount, min_tokens_out, deadline);
+        self.record(result);
+    }
+
+    pub fn go_short_with_slippage(
+        &mut self,
+        account: Account,
+        amount: U256,
+        min_tokens_out: U256,
+        deadline: u64,
+    ) {
+        let address = self.address(account);
+        self.odra_env.set_caller(address);
+        self.wcspr_token.approve(self.market.address(), &amount);
+        let result = self
+            .market
+            .try_deposit_short_with_slippage(amount, min_tokens_out, deadline);
+        self.record(result);
+    }
+
+    pub fn withdraw_long_with_slippage(
+        &mut self,
+        account: Account,
+        amount: U256,
+        min_collateral_out: U256,
+        deadline: u64,
+    ) {
+        let address = self.address(account);
+        self.odra_env.set_caller(address);
+        let result =
+            self.market
+                .try_withdraw_long_with_slippage(amount, min_collateral_out, deadline);
+        self.record(result);
+    }
+
+    pub fn withdraw_short_with_slippage(
+        &mut self,
+        account: Account,
+        amount: U256,
+        min_collateral_out: U256,
+        deadline: u64,
+    ) {
+        let address = self.address(account);
+        self.odra_env.set_caller(address);
+        let result =
+            self.market
+                .try_withdraw_short_with_slippage(amount, min_collateral_out, deadline);
+        self.record(result);
+    }
+
     pub fn set_price(&mut self, price: U256) {
+        self.set_admin_as_caller();
         self.market.set_price(PriceData {
             price,
             timestamp: 0,
         });
     }
 
+    pub fn flip(&mut self, account: Account, side_from: Side, tokens: U256) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_flip(side_from, tokens);
+        self.record(result);
+    }
+
+    pub fn set_flip_fee(&mut self, fee: u64) {
+        self.set_admin_as_caller();
+        let result = self.market.try_set_flip_fee(fee);
+        self.record(result);
+    }
+
+    pub fn preview_flip(&self, account: Account, side_from: Side, tokens: U256) -> TradePreview {
+        self.market
+            .preview_flip(&self.address(account), side_from, tokens)
+    }
+
+    pub fn submit_price(&mut self, account: Account, price: U256, timestamp: u64) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_set_price(PriceData { price, timestamp });
+        self.record(result);
+    }
+
+    pub fn set_keeper(&mut self, account: Account) {
+        self.set_admin_as_caller();
+        let address = self.address(account);
+        self.market.set_keeper(&address, true);
+    }
+
+    pub fn set_keeper_config(&mut self, config: KeeperConfig) {
+        self.set_admin_as_caller();
+        let result = self.market.try_set_keeper_config(config);
+        self.record(result);
+    }
+
+    pub fn keeper_config(&self) -> KeeperConfig {
+        self.market.get_keeper_config()
+    }
+
+    pub fn keeper_earnings(&self, account: Account) -> U256 {
+        self.market.get_keeper_earnings(&self.address(account))
+    }
+
+    pub fn keeper_pool(&self) -> U256 {
+        self.market.get_keeper_pool()
+    }
+
+    pub fn distribute_rewards(&mut self, amount: U256) {
+        // Simulate rewards of an era by minting them to the admin first.
+        let admin = self.odra_env.get_account(0);
+        self.set_admin_as_caller();
+        self.wcspr_token.mint(&admin, &amount);
+        self.wcspr_token.approve(self.market.address(), &amount);
+        self.market.distribute_rewards(amount);
+    }
+
+    pub fn set_rewards_destination(&mut self, destination: RewardsDestination) {
+        self.set_admin_as_caller();
+        self.market.set_rewards_destination(destination);
+    }
+
+    pub fn preview_deposit(&self, side: Side, amount: U256) -> TradePreview {
+        self.market.preview_deposit(side, amount)
+    }
+
+    pub fn preview_withdraw(&self, account: Account, side: Side, tokens: U256) -> TradePreview {
+        self.market
+            .preview_withdraw(&self.address(account), side, tokens)
+    }
+
+    pub fn get_position(&self, account: Account) -> Position {
+        self.market.get_position(&self.address(account))
+    }
+
+    pub fn get_pnl(&self, account: Account) -> PnlReport {
+        self.market.get_pnl(&self.address(account))
+    }
+
     pub fn get_market_state(&self) -> MarketState {
         self.market.get_market_state()
     }
@@ -197,4 +429,707 @@ impl CasperShortsWorld {
             TokenKind::LONG => self.long_token.transfer(&receiver, &amount),
         }
     }
+    pub fn place_order(
+        &mut self,
+        account: Account,
+        side: Side,
+        tokens: U256,
+        trigger: Trigger,
+        trigger_price: U256,
+    ) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self
+            .market
+            .try_place_order(side, tokens, trigger, trigger_price);
+        self.record(result);
+    }
+
+    pub fn cancel_order(&mut self, account: Account, id: u32) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_cancel_order(id);
+        self.record(result);
+    }
+
+    pub fn execute_orders(&mut self, account: Account, max: u32) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_execute_orders(max);
+        self.record(result);
+    }
+
+    pub fn get_order(&self, id: u32) -> Order {
+        self.market.get_order(id)
+    }
+
+    pub fn get_open_orders_of(&self, account: Account) -> Vec<Order> {
+        self.market.get_open_orders_of(&self.address(account))
+    }
+
+    /// Moves the block time forward by `duration` milliseconds.
+    pub fn advance_time(&mut self, duration: u64) {
+        self.odra_env.advance_block_time(duration);
+    }
+
+    pub fn block_time(&self) -> u64 {
+        self.odra_env.block_time()
+    }
+
+    pub fn market_expiry(&self) -> Option<u64> {
+        self.market.get_expiry()
+    }
+
+    pub fn is_market_expired(&self) -> bool {
+        self.market.is_expired()
+    }
+
+    pub fn payoff(&self) -> Payoff {
+        self.market.get_payoff()
+    }
+
+    pub fn final_price(&self) -> Option<U256> {
+        self.market.get_final_price().map(|price| price.price)
+    }
+
+    pub fn settle_market(&mut self, account: Account, price: U256) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_settle(PriceData {
+            price,
+            timestamp: 0,
+        });
+        self.record(result);
+    }
+
+    pub fn sweep_market(&mut self, account: Account, recipient: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let recipient = self.address(recipient);
+        let result = self.market.try_sweep(&recipient);
+        self.record(result);
+    }
+
+    pub fn start_competition(
+        &mut self,
+        start_time: u64,
+        end_time: u64,
+        prizes: Vec<U256>,
+        max_participants: u32,
+    ) {
+        self.set_admin_as_caller();
+        let total = prizes.iter().fold(U256::zero(), |acc, prize| acc + prize);
+        let mut competition = Competition::deploy(
+            &self.odra_env,
+            CompetitionInitArgs {
+                start_time,
+                end_time,
+                prizes,
+                max_participants,
+            },
+        );
+        competition.set_config(self.config());
+
+        // The organizer escrows the prize pool.
+        let admin = self.odra_env.get_account(0);
+        self.wcspr_token.mint(&admin, &total);
+        self.wcspr_token.approve(competition.address(), &total);
+        competition.fund_prizes();
+        self.competition = Some(competition);
+    }
+
+    pub fn register_for_competition(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.competition_mut().try_register();
+        self.record(result);
+    }
+
+    pub fn finalize_competition(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.competition_mut().try_finalize();
+        self.record(result);
+    }
+
+    pub fn competition_ranking(&self) -> Vec<Standing> {
+        self.competition
+            .as_ref()
+            .expect("Competition not started")
+            .get_ranking()
+    }
+
+    fn competition_mut(&mut self) -> &mut CompetitionHostRef {
+        self.competition.as_mut().expect("Competition not started")
+    }
+
+    fn config(&self) -> Config {
+        Config {
+            wcspr_token: *self.wcspr_token.address(),
+            short_token: *self.short_token.address(),
+            long_token: *self.long_token.address(),
+            market: *self.market.address(),
+            fee_collector: self.address(Account::FeeCollector),
+        }
+    }
+
+    pub fn try_transfer(
+        &mut self,
+        token: TokenKind,
+        sender: Account,
+        amount: U256,
+        receiver: Account,
+    ) {
+        let sender = self.address(sender);
+        let receiver = self.address(receiver);
+        self.odra_env.set_caller(sender);
+        let result = match token {
+            TokenKind::WCSPR => self.wcspr_token.try_transfer(&receiver, &amount),
+            TokenKind::SHORT => self.short_token.try_transfer(&receiver, &amount),
+            TokenKind::LONG => self.long_token.try_transfer(&receiver, &amount),
+        };
+        self.record(result);
+    }
+
+    pub fn update_limits(&mut self, update: impl FnOnce(&mut MarketLimits)) {
+        let mut limits = self.market.get_limits();
+        update(&mut limits);
+        self.set_admin_as_caller();
+        self.market.set_limits(limits);
+    }
+
+    pub fn set_imbalance_guard(&mut self, guard: ImbalanceGuard) {
+        self.set_admin_as_caller();
+        let result = self.market.try_set_imbalance_guard(guard);
+        self.record(result);
+    }
+
+    pub fn deposit_capacity(&self, side: Side) -> U256 {
+        self.market.get_deposit_capacity(side)
+    }
+
+    pub fn set_insurance_fee_share(&mut self, account: Account, fee_share: u64) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_set_insurance_fee_share(fee_share);
+        self.record(result);
+    }
+
+    pub fn insurance_fund(&self) -> U256 {
+        self.market.get_insurance_fund()
+    }
+
+    pub fn insurance_payouts(&self, reason: PayoutReason) -> Vec<Payout> {
+        self.market
+            .get_insurance_payouts()
+            .into_iter()
+            .filter(|payout| payout.reason == reason)
+            .collect()
+    }
+
+    pub fn accounting_deficit(&self) -> U256 {
+        self.market.get_accounting_deficit()
+    }
+
+    pub fn cover_deficit(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        self.market.cover_deficit();
+    }
+
+    /// Simulates WCSPR leaving the market without being accounted for.
+    pub fn lose_market_wcspr(&mut self, amount: U256) {
+        // Let the admin act as the market for the WCSPR token for a moment
+        // to move the market's WCSPR out.
+        let admin = self.odra_env.get_account(0);
+        let market = *self.market.address();
+        self.swap_wcspr_config(Config {
+            market: admin,
+            ..self.config()
+        });
+        self.set_admin_as_caller();
+        self.wcspr_token.transfer_from(&market, &admin, &amount);
+        self.swap_wcspr_config(self.config());
+    }
+
+    fn swap_wcspr_config(&mut self, config: Config) {
+        self.set_admin_as_caller();
+        self.wcspr_token.propose_config(config);
+        self.odra_env
+            .advance_block_time(self.wcspr_token.get_config_delay());
+        self.wcspr_token.execute_config();
+    }
+
+    pub fn set_deferred_settlement(&mut self, enabled: bool) {
+        self.set_admin_as_caller();
+        self.market.set_deferred_settlement(enabled);
+    }
+
+    pub fn pending_requests_of(&self, account: Account) -> Vec<PendingRequest> {
+        self.market.get_pending_requests_of(&self.address(account))
+    }
+
+    pub fn claimable(&self, account: Account) -> Claimable {
+        self.market.get_claimable(&self.address(account))
+    }
+
+    pub fn settle_requests(&mut self, account: Account, max: u32) {
+        self.odra_env.set_caller(self.address(account));
+        self.market.settle_requests(max);
+    }
+
+    pub fn claim(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_claim();
+        self.record(result);
+    }
+
+    pub fn set_early_exit_fee(&mut self, account: Account, early_exit_fee: EarlyExitFee) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_set_early_exit_fee(early_exit_fee);
+        self.record(result);
+    }
+
+    pub fn set_paused(&mut self, account: Account, paused: bool) {
+        self.odra_env.set_caller(self.address(account));
+        let result = if paused {
+            self.market.try_pause()
+        } else {
+            self.market.try_unpause()
+        };
+        self.record(result);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.market.is_paused()
+    }
+
+    pub fn exit_unlock_time(&self, account: Account, side: Side) -> u64 {
+        self.market
+            .get_exit_unlock_time(&self.address(account), side)
+    }
+
+    pub fn register_market(&mut self, account: Account, price_feed: Account) {
+        let config = self.config();
+        self.register_market_with(account, config, price_feed);
+    }
+
+    /// Deploys another market without wiring it, except for the Market's
+    /// own config if `configure_market` is set, and registers it.
+    pub fn register_unwired_market(&mut self, account: Account, configure_market: bool) {
+        let mut next = self.deploy_market_contracts();
+        let config = self.config_of(&next);
+        if configure_market {
+            next.market.set_config(config.clone());
+        }
+        self.register_market_with(account, config, Account::Admin);
+    }
+
+    /// Deploys a second market wired like the first one, with Admin as its
+    /// keeper, but not routed by the WCSPR token.
+    pub fn deploy_next_market(&mut self) {
+        let mut next = self.deploy_market_contracts();
+        let config = self.config_of(&next);
+        next.market.set_config(config.clone());
+        next.long_token.set_config(config.clone());
+        next.short_token.set_config(config);
+        let market = *next.market.address();
+        next.long_token
+            .change_security(vec![], vec![market], vec![]);
+        next.short_token
+            .change_security(vec![], vec![market], vec![]);
+        next.market.set_keeper(&self.address(Account::Admin), true);
+        self.next_market = Some(next);
+    }
+
+    pub fn propose_next_market(&mut self, account: Account) {
+        let config = self.next_market_config();
+        self.odra_env.set_caller(self.address(account));
+        let result = self.wcspr_token.try_propose_market(config);
+        self.record(result);
+    }
+
+    pub fn add_next_market(&mut self, account: Account) {
+        let market = self.next_market_config().market;
+        self.odra_env.set_caller(self.address(account));
+        let result = self.wcspr_token.try_add_market(market);
+        self.record(result);
+    }
+
+    pub fn register_next_market(&mut self, account: Account) {
+        let config = self.next_market_config();
+        self.register_market_with(account, config, Account::Admin);
+    }
+
+    /// Transfers WCSPR to the LONG token of the next market.
+    pub fn transfer_to_next_long(&mut self, account: Account, amount: U256) {
+        let recipient = self.next_market_config().long_token;
+        self.odra_env.set_caller(self.address(account));
+        let result = self.wcspr_token.try_transfer(&recipient, &amount);
+        self.record(result);
+    }
+
+    pub fn next_long_balance(&self, account: Account) -> U256 {
+        let next = self.next_market.as_ref().expect("next market not deployed");
+        next.long_token.balance_of(&self.address(account))
+    }
+
+    fn next_market_config(&self) -> Config {
+        let next = self.next_market.as_ref().expect("next market not deployed");
+        self.config_of(next)
+    }
+
+    fn config_of(&self, next: &NextMarket) -> Config {
+        Config {
+            short_token: *next.short_token.address(),
+            long_token: *next.long_token.address(),
+            market: *next.market.address(),
+            ..self.config()
+        }
+    }
+
+    fn deploy_market_contracts(&self) -> NextMarket {
+        self.set_admin_as_caller();
+        let short_token = TokenShort::deploy(
+            &self.odra_env,
+            TokenShortInitArgs {
+                name: "NEW_SHORT".to_string(),
+                symbol: "NEW_SHORT".to_string(),
+                decimals: 9,
+                initial_supply: 0u64.into(),
+            },
+        );
+        let long_token = TokenLong::deploy(
+            &self.odra_env,
+            TokenLongInitArgs {
+                name: "NEW_LONG".to_string(),
+                symbol: "NEW_LONG".to_string(),
+                decimals: 9,
+                initial_supply: 0u64.into(),
+            },
+        );
+        let market = Market::deploy(
+            &self.odra_env,
+            MarketInitArgs {
+                last_price: PriceData {
+                    price: ONE_CENT.into(),
+                    timestamp: 0u64,
+                },
+                expiry: None,
+                payoff: Payoff::Linear,
+            },
+        );
+        NextMarket {
+            market,
+            long_token,
+            short_token,
+        }
+    }
+
+    pub fn registry_market_count(&mut self) -> u32 {
+        self.registry_mut().get_market_count()
+    }
+
+    pub fn propose_fee_collector(&mut self, account: Account, fee_collector: Account) {
+        let config = Config {
+            fee_collector: self.address(fee_collector),
+            ..self.config()
+        };
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_propose_config(config);
+        self.record(result);
+    }
+
+    pub fn propose_wcspr_market(&mut self, account: Account, market: Account) {
+        let config = Config {
+            market: self.address(market),
+            ..self.config()
+        };
+        self.odra_env.set_caller(self.address(account));
+        let result = self.wcspr_token.try_propose_config(config);
+        self.record(result);
+    }
+
+    pub fn execute_config(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_execute_config();
+        self.record(result);
+    }
+
+    pub fn execute_wcspr_config(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.wcspr_token.try_execute_config();
+        self.record(result);
+    }
+
+    pub fn cancel_config(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_cancel_config();
+        self.record(result);
+    }
+
+    pub fn reset_config(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_set_config(self.config());
+        self.record(result);
+    }
+
+    pub fn set_config_delay(&mut self, account: Account, delay: u64) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_set_config_delay(delay);
+        self.record(result);
+    }
+
+    pub fn config_delay(&self) -> u64 {
+        self.market.get_config_delay()
+    }
+
+    pub fn pending_config_eta(&self) -> Option<u64> {
+        self.market.get_pending_config().map(|pending| pending.eta)
+    }
+
+    pub fn market_emitted(&self, event: &str) -> bool {
+        self.odra_env.emitted(self.market.address(), event)
+    }
+
+    /// Deploys a multisig and makes it the owner of the Market and the
+    /// tokens.
+    pub fn install_multisig(&mut self, signers: Vec<Account>, threshold: u32) {
+        let signers = signers
+            .into_iter()
+            .map(|signer| self.address(signer))
+            .collect();
+        self.set_admin_as_caller();
+        let result =
+            Multisig::try_deploy(&self.odra_env, MultisigInitArgs { signers, threshold });
+        let multisig = match result {
+            Ok(multisig) => multisig,
+            Err(error) => {
+                self.last_error = Some(error);
+                return;
+            }
+        };
+        let owner = *multisig.address();
+        self.market.transfer_ownership(&owner);
+        self.long_token.transfer_ownership(&owner);
+        self.short_token.transfer_ownership(&owner);
+        self.wcspr_token.transfer_ownership(&owner);
+        self.multisig = Some(multisig);
+        self.last_error = None;
+    }
+
+    pub fn propose_market_call(&mut self, account: Account, entry_point: &str, args: RuntimeArgs) {
+        let target = *self.market.address();
+        self.propose_call(account, target, entry_point, args);
+    }
+
+    pub fn propose_wcspr_call(&mut self, account: Account, entry_point: &str, args: RuntimeArgs) {
+        let target = *self.wcspr_token.address();
+        self.propose_call(account, target, entry_point, args);
+    }
+
+    pub fn propose_multisig_call(
+        &mut self,
+        account: Account,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) {
+        let target = *self.multisig_mut().address();
+        self.propose_call(account, target, entry_point, args);
+    }
+
+    pub fn approve_proposal(&mut self, account: Account, id: u32) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.multisig_mut().try_approve(id);
+        self.record(result);
+    }
+
+    pub fn revoke_approval(&mut self, account: Account, id: u32) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.multisig_mut().try_revoke_approval(id);
+        self.record(result);
+    }
+
+    pub fn execute_proposal(&mut self, account: Account, id: u32) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.multisig_mut().try_execute(id);
+        self.record(result);
+    }
+
+    pub fn set_signers_directly(
+        &mut self,
+        account: Account,
+        signers: Vec<Account>,
+        threshold: u32,
+    ) {
+        let signers = signers
+            .into_iter()
+            .map(|signer| self.address(signer))
+            .collect();
+        self.odra_env.set_caller(self.address(account));
+        let result = self.multisig_mut().try_set_signers(signers, threshold);
+        self.record(result);
+    }
+
+    pub fn proposal_approvals(&mut self, id: u32) -> usize {
+        self.multisig_mut().get_approvals(id).len()
+    }
+
+    pub fn multisig_signers(&mut self) -> Vec<Address> {
+        self.multisig_mut().get_signers()
+    }
+
+    pub fn flip_fee(&self) -> u64 {
+        self.market.get_flip_fee()
+    }
+
+    pub fn wcspr_config_delay(&self) -> u64 {
+        self.wcspr_token.get_config_delay()
+    }
+
+    fn propose_call(
+        &mut self,
+        account: Account,
+        target: Address,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) {
+        let args = args.to_bytes().unwrap().into();
+        self.odra_env.set_caller(self.address(account));
+        let result = self
+            .multisig_mut()
+            .try_propose(target, entry_point.to_string(), args);
+        self.record(result);
+    }
+
+    fn multisig_mut(&mut self) -> &mut MultisigHostRef {
+        self.multisig.as_mut().expect("Multisig not installed")
+    }
+
+    /// Deploys a market whose tokens call back into it, and gives Alice
+    /// and Bob their collateral.
+    pub fn deploy_reentrant_market(&mut self) {
+        self.set_admin_as_caller();
+        let wcspr_token = ReentrantToken::deploy(&self.odra_env, NoArgs);
+        let long_token = ReentrantToken::deploy(&self.odra_env, NoArgs);
+        let short_token = ReentrantToken::deploy(&self.odra_env, NoArgs);
+        let mut market = Market::deploy(
+            &self.odra_env,
+            MarketInitArgs {
+                last_price: PriceData {
+                    price: ONE_CENT.into(),
+                    timestamp: 0u64,
+                },
+                expiry: None,
+                payoff: Payoff::Linear,
+            },
+        );
+        market.set_config(Config {
+            wcspr_token: *wcspr_token.address(),
+            short_token: *short_token.address(),
+            long_token: *long_token.address(),
+            market: *market.address(),
+            fee_collector: self.address(Account::FeeCollector),
+        });
+        let mut reentrant = ReentrantMarket {
+            market,
+            wcspr_token,
+            long_token,
+            short_token,
+        };
+        for account in [Account::Alice, Account::Bob] {
+            reentrant
+                .wcspr_token
+                .mint(&self.address(account), &U256::from(INITIAL_WCSPR_BALANCE));
+        }
+        self.reentrant = Some(reentrant);
+    }
+
+    pub fn arm_reentrant_token(&mut self, token: TokenKind, attack: Attack) {
+        let reentrant = self
+            .reentrant
+            .as_mut()
+            .expect("Reentrant market not deployed");
+        let market = *reentrant.market.address();
+        match token {
+            TokenKind::WCSPR => reentrant.wcspr_token.arm(market, attack),
+            TokenKind::LONG => reentrant.long_token.arm(market, attack),
+            TokenKind::SHORT => reentrant.short_token.arm(market, attack),
+        }
+    }
+
+    pub fn go_long_on_reentrant_market(&mut self, account: Account, amount: U256) {
+        self.odra_env.set_caller(self.address(account));
+        let reentrant = self
+            .reentrant
+            .as_mut()
+            .expect("Reentrant market not deployed");
+        let result = reentrant.market.try_deposit_long(amount);
+        self.record(result);
+    }
+
+    pub fn withdraw_long_on_reentrant_market(&mut self, account: Account, amount: U256) {
+        self.odra_env.set_caller(self.address(account));
+        let reentrant = self
+            .reentrant
+            .as_mut()
+            .expect("Reentrant market not deployed");
+        let result = reentrant.market.try_withdraw_long(amount);
+        self.record(result);
+    }
+
+    pub fn reentrant_balance_of(&self, token: TokenKind, account: Account) -> U256 {
+        let reentrant = self
+            .reentrant
+            .as_ref()
+            .expect("Reentrant market not deployed");
+        let address = self.address(account);
+        match token {
+            TokenKind::WCSPR => reentrant.wcspr_token.balance_of(&address),
+            TokenKind::LONG => reentrant.long_token.balance_of(&address),
+            TokenKind::SHORT => reentrant.short_token.balance_of(&address),
+        }
+    }
+
+    pub fn state_versions(&self) -> Vec<u32> {
+        vec![
+            self.market.get_state_version(),
+            self.long_token.get_state_version(),
+            self.short_token.get_state_version(),
+            self.wcspr_token.get_state_version(),
+        ]
+    }
+
+    pub fn migrate_market(&mut self, account: Account) {
+        self.odra_env.set_caller(self.address(account));
+        let result = self.market.try_migrate();
+        self.record(result);
+    }
+
+    pub fn migrate_token(&mut self, account: Account, token: TokenKind) {
+        self.odra_env.set_caller(self.address(account));
+        let result = match token {
+            TokenKind::LONG => self.long_token.try_migrate(),
+            TokenKind::SHORT => self.short_token.try_migrate(),
+            TokenKind::WCSPR => self.wcspr_token.try_migrate(),
+        };
+        self.record(result);
+    }
+
+    fn register_market_with(&mut self, account: Account, config: Config, price_feed: Account) {
+        let price_feed = self.address(price_feed);
+        // Deploy the registry first, as the deployment changes the caller.
+        self.registry_mut();
+        self.odra_env.set_caller(self.address(account));
+        let result = self.registry_mut().try_register_market(config, price_feed);
+        self.record(result);
+    }
+
+    fn registry_mut(&mut self) -> &mut MarketRegistryHostRef {
+        if self.registry.is_none() {
+            self.set_admin_as_caller();
+            let registry = MarketRegistry::deploy(&self.odra_env, NoArgs);
+            self.registry = Some(registry);
+        }
+        self.registry.as_mut().unwrap()
+    }
+
+    fn record<T>(&mut self, result: OdraResult<T>) {
+        self.last_error = result.err();
+    }
+
+    fn set_admin_as_caller(&self) {
+        self.odra_env.set_caller(self.odra_env.get_account(0));
+    }
 }
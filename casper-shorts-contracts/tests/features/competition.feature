@@ -0,0 +1,82 @@
+Feature: Trading competition
+
+    Background:
+        Given a competition runs from 200 to 1000 with prizes of 50, 30 and 20 WCSPR
+
+    Scenario: Top participants get the prizes
+        When Alice registers for the competition
+        Then the last call fails with CompetitionNotStarted
+
+        When the block time is 200
+        When Alice registers for the competition
+        When Bob registers for the competition
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+
+        When the competition is finalized
+        Then the last call fails with CompetitionNotEnded
+
+        When the block time is 1000
+        When Charlie registers for the competition
+        Then the last call fails with CompetitionEnded
+
+        When the competition is finalized
+        Then the last call succeeds
+        Then 2 participants are ranked
+        Then Alice finishes the competition with 1016.808 WCSPR
+        Then Bob finishes the competition with 979.202 WCSPR
+        Then Alice places 1 in the competition with a prize of 50 WCSPR
+        Then Bob places 2 in the competition with a prize of 30 WCSPR
+        Then Alice has 750 WCSPR
+        Then Bob has 930 WCSPR
+        Then Admin has 20 WCSPR
+
+    Scenario: Participants are ranked by their final balance, not its growth
+        When Bob transfers 500 WCSPR to Alice
+        When the block time is 200
+        When Alice registers for the competition
+        When Bob registers for the competition
+        When Bob goes long with 300 WCSPR
+        When Alice goes short with 100 WCSPR
+        When price changes to 0.012 USD
+
+        When the block time is 1000
+        When the competition is finalized
+        Then Alice finishes the competition with 1479.202 WCSPR
+        Then Bob finishes the competition with 516.808 WCSPR
+        Then Alice places 1 in the competition with a prize of 50 WCSPR
+        Then Bob places 2 in the competition with a prize of 30 WCSPR
+
+    Scenario: Anyone snapshots the balances at the end
+        When the block time is 200
+        When Alice registers for the competition
+        When Alice goes long with 300 WCSPR
+
+        When the block time is 1000
+        When Bob finalizes the competition
+        Then the last call succeeds
+        Then Alice places 1 in the competition with a prize of 50 WCSPR
+        When price changes to 0.012 USD
+        When Alice transfers 700 WCSPR to Bob
+        Then Alice finishes the competition with 997.0075 WCSPR
+
+    Scenario: Registration and finalization happen once
+        When the block time is 200
+        When Alice registers for the competition
+        When Alice registers for the competition
+        Then the last call fails with AlreadyRegistered
+
+        When the block time is 1000
+        When the competition is finalized
+        Then the last call succeeds
+        When the competition is finalized
+        Then the last call fails with AlreadyFinalized
+
+    Scenario: Registration is limited
+        Given a competition for 2 participants runs from 200 to 1000 with prizes of 50 WCSPR
+        When the block time is 200
+        When Alice registers for the competition
+        When Bob registers for the competition
+        When Charlie registers for the competition
+        Then the last call fails with TooManyParticipants
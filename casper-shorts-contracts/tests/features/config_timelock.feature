@@ -0,0 +1,66 @@
+Feature: Timelocked config changes
+
+    Scenario: A config change is applied after the delay
+        Then the config delay is 172800000
+        When Admin proposes Charlie as the fee collector
+        Then the last call succeeds
+        Then a config change is pending
+        Then the market emitted ConfigChangeProposed
+        When half of the config delay passes
+        When Bob executes the config change
+        Then the last call fails with ConfigChangeNotReady
+        When Alice goes long with 100 WCSPR
+        Then FeeCollector has 0.5 WCSPR
+        When half of the config delay passes
+        When Bob executes the config change
+        Then the last call succeeds
+        Then no config change is pending
+        Then the market emitted ConfigChangeExecuted
+        When Alice goes long with 100 WCSPR
+        Then FeeCollector has 0.5 WCSPR
+        Then Charlie has 0.5 WCSPR
+
+    Scenario: Only one change can be pending
+        When Admin proposes Charlie as the fee collector
+        When Admin proposes Bob as the fee collector
+        Then the last call fails with ConfigChangePending
+
+    Scenario: The owner cancels a pending change
+        When Alice proposes Alice as the fee collector
+        Then the last call fails with CallerNotTheOwner
+        When Admin proposes Charlie as the fee collector
+        When Alice cancels the config change
+        Then the last call fails with CallerNotTheOwner
+        When Admin cancels the config change
+        Then the last call succeeds
+        Then no config change is pending
+        Then the market emitted ConfigChangeCancelled
+        When the config delay passes
+        When Bob executes the config change
+        Then the last call fails with NoPendingConfig
+        When Admin cancels the config change
+        Then the last call fails with NoPendingConfig
+
+    Scenario: The config is set directly only once
+        When Admin sets the config again
+        Then the last call fails with ConfigAlreadySet
+
+    Scenario: The delay can only be raised
+        When Alice sets the config delay to 259200000
+        Then the last call fails with CallerNotTheOwner
+        When Admin sets the config delay to 86400000
+        Then the last call fails with ConfigDelayTooShort
+        When Admin sets the config delay to 259200000
+        Then the last call succeeds
+        Then the config delay is 259200000
+        Then the market emitted ConfigDelayChanged
+
+    Scenario: Token configs are timelocked too
+        When Alice proposes Alice as the market of the WCSPR token
+        Then the last call fails with CallerNotTheOwner
+        When Admin proposes Charlie as the market of the WCSPR token
+        When Bob executes the WCSPR config change
+        Then the last call fails with ConfigChangeNotReady
+        When the config delay passes
+        When Bob executes the WCSPR config change
+        Then the last call succeeds
@@ -0,0 +1,117 @@
+Feature: Deferred settlement
+
+    Background:
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        Given the settlement is deferred
+
+    Scenario: Deposits are settled at the next price
+        When Alice goes long with 100 WCSPR
+        Then Alice has 800 WCSPR
+        Then Alice has 99.5 LONG
+        Then the queue holds 1 trade of Alice
+        Then MarketContract has 299 WCSPR
+        Then the accounting deficit is 0 WCSPR
+
+        When price changes to 0.012 USD
+        Then the queue holds 0 trades of Alice
+        Then Alice can claim 82.916666666 LONG
+        Then the position of Alice is 182.416666666 LONG worth 217.8055 WCSPR
+        When Alice claims
+        Then Alice has 182.416666666 LONG
+        Then Alice can claim 0 LONG
+
+    Scenario: Withdrawals are settled at the next price
+        When Bob withdraws 99.5 SHORT
+        Then Bob has 0 SHORT
+        Then Bob has 900 WCSPR
+        Then the queue holds 1 trade of Bob
+        Then the position of Bob is 99.5 SHORT worth 99.0025 WCSPR
+
+        When price changes to 0.012 USD
+        Then Bob can claim 79.202 WCSPR
+        Then the accounting deficit is 0 WCSPR
+        When Bob claims
+        Then Bob has 979.202 WCSPR
+        When Bob claims
+        Then the last call fails with NothingToClaim
+
+    Scenario: Front-running a known price doesn't pay
+        When Alice goes long with 100 WCSPR
+        When price changes to 0.012 USD
+        When Alice claims
+        When Alice withdraws 82.916666666 LONG
+        When price changes to 0.012 USD
+        When Alice claims
+        Then Alice has 99.5 LONG
+        Then Alice has 899.0025 WCSPR
+
+    Scenario: Trades made after a price update wait for the next one
+        When price changes to 0.012 USD
+        When Alice goes long with 100 WCSPR
+        When Charlie settles at most 10 queued trades
+        Then the queue holds 1 trade of Alice
+        When price changes to 0.012 USD
+        Then the queue holds 0 trades of Alice
+
+    Scenario: Deposits rejected by the limits are refunded
+        Given the liquidity of each side is capped at 150 WCSPR
+        When Alice goes long with 100 WCSPR
+        Then the last call succeeds
+        When price changes to 0.01 USD
+        Then Alice can claim 100 WCSPR
+        Then Alice can claim 0 LONG
+        When Alice claims
+        Then Alice has 900 WCSPR
+
+    Scenario: Deposits into a wiped out side are refunded
+        When price changes to 0.004 USD
+        Then the position of Alice is 99.5 LONG worth 0 WCSPR
+        When Alice goes long with 100 WCSPR
+        Then the last call succeeds
+        When price changes to 0.003 USD
+        Then Alice can claim 100 WCSPR
+        Then Alice can claim 0 LONG
+        When the settlement is immediate again
+        When Alice goes long with 100 WCSPR for at least 0 LONG until 2000
+        Then the last call fails with SideWipedOut
+
+    Scenario: Queued trades of an account are capped
+        When Alice queues 5 deposits of 1 WCSPR
+        Then the queue holds 5 trades of Alice
+        When Alice goes long for Alice with 1 WCSPR
+        Then the last call fails with TooManyQueuedRequests
+        When Alice redeems 1 LONG to Alice
+        Then the last call fails with TooManyQueuedRequests
+
+        When price changes to 0.01 USD
+        Then the queue holds 0 trades of Alice
+        Then Alice can claim 4.975 LONG
+        When Alice goes long for Alice with 1 WCSPR
+        Then the last call succeeds
+
+    Scenario: The queue holds what a price update settles
+        When Bob transfers 100 WCSPR to Charlie
+        When Bob transfers 100 WCSPR to Admin
+        When Admin queues 5 deposits of 1 WCSPR
+        When Alice queues 5 deposits of 1 WCSPR
+        When Bob queues 5 deposits of 1 WCSPR
+        When Charlie queues 5 deposits of 1 WCSPR
+        When FeeCollector goes long for FeeCollector with 0.5 WCSPR
+        Then the last call fails with TooManyQueuedRequests
+
+        When price changes to 0.01 USD
+        Then the queue holds 0 trades of Alice
+        Then the queue holds 0 trades of Charlie
+        Then Charlie can claim 4.975 LONG
+        When FeeCollector goes long for FeeCollector with 0.5 WCSPR
+        Then the last call succeeds
+
+    Scenario: Trades with slippage limits and flips need immediate settlement
+        When Alice goes long with 10 WCSPR for at least 1 LONG until 200
+        Then the last call fails with SettlementIsDeferred
+        When Alice flips 10 LONG
+        Then the last call fails with SettlementIsDeferred
+        When the settlement is immediate again
+        When Alice flips 10 LONG
+        Then the last call succeeds
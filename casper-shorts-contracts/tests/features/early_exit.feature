@@ -0,0 +1,60 @@
+Feature: Early-exit fee
+
+    Background:
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 300 WCSPR
+        When 3600 seconds pass
+
+    Scenario: Front-running a known price pays without the early-exit fee
+        When Alice goes long with 100 WCSPR
+        When price changes to 0.011 USD
+        When Alice withdraws 99.5 LONG
+        Then Alice has 913.852875 WCSPR
+
+    Scenario: The early-exit fee makes front-running unprofitable
+        Given withdrawals within 3600 seconds of a deposit pay an extra 1500 basis points
+        When Alice goes long with 100 WCSPR
+        Then Alice can exit LONG without the early-exit fee from 7200100
+        When price changes to 0.011 USD
+        When Alice withdraws 99.5 LONG
+        Then Alice has 896.774943750 WCSPR
+        Then the position of Bob is 298.5 SHORT worth 284.38468125 WCSPR
+
+    Scenario: Withdrawals after the holding period pay no extra fee
+        Given withdrawals within 3600 seconds of a deposit pay an extra 1500 basis points
+        When Alice goes long with 100 WCSPR
+        When 3600 seconds pass
+        When Alice withdraws 99.5 LONG
+        Then Alice has 899.0025 WCSPR
+
+    Scenario: Transferred tokens keep the last deposit time
+        Given withdrawals within 3600 seconds of a deposit pay an extra 1500 basis points
+        When Alice goes long with 100 WCSPR
+        When Alice transfers 99.5 LONG to Charlie
+        Then Charlie can exit LONG without the early-exit fee from 7200100
+        When Charlie withdraws 99.5 LONG
+        Then Charlie has 84.152125 WCSPR
+
+    Scenario: Only the owner sets a valid early-exit fee
+        When Alice sets the early-exit fee to 100 basis points
+        Then the last call fails with CallerNotTheOwner
+        When Admin sets the early-exit fee to 10001 basis points
+        Then the last call fails with EarlyExitFeeTooHigh
+
+    Scenario: Previews include the early-exit fee
+        Given withdrawals within 3600 seconds of a deposit pay an extra 1500 basis points
+        When Alice goes long with 100 WCSPR
+        Then withdrawing 99.5 LONG of Alice previews 84.152125 WCSPR and 0.422875 WCSPR fee
+        Then flipping 99.5 LONG of Alice previews 84.152125 tokens and 0.422875 WCSPR fee
+        Then LONG previews of Alice for 99.5 tokens include 14.925 WCSPR early-exit fee
+        When Alice flips 99.5 LONG
+        Then Alice has 84.152125 SHORT
+
+    Scenario: The early-exit fee of a flip stays with the side being left
+        Given withdrawals within 3600 seconds of a deposit pay an extra 1500 basis points
+        When Bob goes long with 100 WCSPR
+        When Alice goes long with 100 WCSPR
+        Then the position of Bob is 99.5 LONG worth 84.152125 WCSPR
+        When Alice flips 99.5 LONG
+        Then Alice has 84.152125 SHORT
+        Then the position of Bob is 99.5 LONG worth 90.463534375 WCSPR
@@ -0,0 +1,59 @@
+Feature: Dated markets
+
+    Scenario: Markets are perpetual by default
+        Then the market is perpetual
+        When the block time is 1000000
+        Then the market is not expired
+
+    Scenario: A dated market stops at its expiry
+        Given a market expiring at 1000
+        Then the market expires at 1000
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        Then the market is not expired
+
+        When the block time is 1000
+        Then the market expired at 0.012 USD
+        When Admin submits price 0.011 USD at 1000
+        Then the last call fails with MarketExpired
+        When Alice goes long with 10 WCSPR for at least 0 LONG until 2000
+        Then the last call fails with MarketExpired
+        When Alice flips 10 LONG
+        Then the last call fails with MarketExpired
+        Then the LONG side can accept 0 WCSPR
+        Then price is 0.012 USD
+
+    Scenario: Holders redeem at the settled liquidity without fees
+        Given a market expiring at 1000
+        Given withdrawals within 10000 seconds of a deposit pay an extra 100 basis points
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        When the block time is 1000
+        Then withdrawing 99.5 LONG of Alice previews 119.4 WCSPR and 0 WCSPR fee
+        When Alice withdraws 99.5 LONG
+        When Bob withdraws 99.5 SHORT
+        Then Alice has 1019.4 WCSPR
+        Then Bob has 979.6 WCSPR
+        Then MarketContract has 0 WCSPR
+
+    Scenario: Queued trades settle at the final price
+        Given a market expiring at 1000
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        Given the settlement is deferred
+        When Alice goes long with 100 WCSPR
+        When Bob withdraws 99.5 SHORT
+        When the block time is 1000
+        When Charlie settles at most 10 queued trades
+        Then the queue holds 0 trades of Alice
+        Then the queue holds 0 trades of Bob
+        Then Alice can claim 100 WCSPR
+        Then Alice can claim 0 LONG
+        Then Bob can claim 99.5 WCSPR
+
+        When Alice withdraws 99.5 LONG
+        Then Alice has 899.5 WCSPR
+        When Alice claims
+        Then Alice has 999.5 WCSPR
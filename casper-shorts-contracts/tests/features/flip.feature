@@ -0,0 +1,32 @@
+Feature: Position flip
+
+    Background:
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+
+    Scenario: Flip pays the fee once
+        Then flipping 100 LONG of Alice previews 99.5 tokens and 0.5 WCSPR fee
+        When Alice flips 100 LONG
+        Then the last call succeeds
+        Then Alice has 198.5 LONG
+        Then Alice has 99.5 SHORT
+        Then Alice has 700 WCSPR
+        Then FeeCollector has 2.5 WCSPR
+
+    Scenario: Flip fee is configurable
+        When the flip fee is set to 10 basis points
+        Then the last call succeeds
+        When Alice flips 100 LONG
+        Then Alice has 99.9 SHORT
+        When Alice flips 99.9 SHORT
+        Then Alice has 0 SHORT
+        Then Alice has 298.3001 LONG
+        Then FeeCollector has 2.1999 WCSPR
+
+    Scenario: Flip fee can't exceed the trading fee
+        When the flip fee is set to 60 basis points
+        Then the last call fails with FlipFeeTooHigh
+
+    Scenario: Flip requires the tokens
+        When Bob flips 100 LONG
+        Then the last call fails with InsufficientBalance
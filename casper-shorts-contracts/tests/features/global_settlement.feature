@@ -0,0 +1,82 @@
+Feature: Global settlement
+
+    Background:
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+
+    Scenario: The owner settles the market at a final price
+        When Alice settles the market at 0.012 USD
+        Then the last call fails with CallerNotTheOwner
+        Then the market is not expired
+
+        When Admin settles the market at 0.012 USD
+        Then the last call succeeds
+        Then the market emitted MarketSettled
+        Then the market expired at 0.012 USD
+        When Admin submits price 0.011 USD at 100
+        Then the last call fails with MarketExpired
+        When Admin settles the market at 0.011 USD
+        Then the last call fails with MarketExpired
+        When Alice goes long with 10 WCSPR for at least 0 LONG until 2000
+        Then the last call fails with MarketExpired
+
+    Scenario: LONG and SHORT redeem at a fixed rate
+        When Admin settles the market at 0.012 USD
+        Then withdrawing 99.5 LONG of Alice previews 119.4 WCSPR and 0 WCSPR fee
+        When Alice withdraws 49.75 LONG
+        Then Alice has 959.7 WCSPR
+        Then withdrawing 49.75 LONG of Alice previews 59.7 WCSPR and 0 WCSPR fee
+        When staking rewards of 10 WCSPR are distributed
+        Then withdrawing 49.75 LONG of Alice previews 59.7 WCSPR and 0 WCSPR fee
+        Then FeeCollector has 11 WCSPR
+        When Bob withdraws 99.5 SHORT
+        Then Bob has 979.6 WCSPR
+
+    Scenario: Unredeemed liquidity is swept after the grace period
+        When Admin sweeps the market to Charlie
+        Then the last call fails with MarketNotExpired
+        When Admin settles the market at 0.012 USD
+        When Alice withdraws 99.5 LONG
+        Then Alice has 1019.4 WCSPR
+        When Admin sweeps the market to Charlie
+        Then the last call fails with GracePeriodNotOver
+
+        When the sweep grace period passes
+        When Alice sweeps the market to Charlie
+        Then the last call fails with CallerNotTheOwner
+        When Admin sweeps the market to Charlie
+        Then the last call succeeds
+        Then the market emitted LiquiditySwept
+        Then Charlie has 79.6 WCSPR
+        Then MarketContract has 0 WCSPR
+        Then withdrawing 99.5 SHORT of Bob previews 0 WCSPR and 0 WCSPR fee
+        Then the accounting deficit is 0 WCSPR
+
+    Scenario: The sweep empties the market
+        Given the insurance fund gets 1000 basis points of fees
+        Given keepers get 1000 basis points of fees, 0 WCSPR per price update and 0 WCSPR per order, up to 0 WCSPR every 0 milliseconds
+        When Bob goes short with 100 WCSPR
+        Then the insurance fund holds 0.045 WCSPR
+        Then the keeper pool holds 0.05 WCSPR
+        Given the settlement is deferred
+        When Alice goes long with 100 WCSPR
+        When Bob withdraws 49.75 SHORT
+        When Bob transfers 1 WCSPR to MarketContract
+        When Admin settles the market at 0.012 USD
+        When the sweep grace period passes
+        When Admin sweeps the market to Charlie
+        Then the last call fails with RequestsPending
+
+        When Alice settles at most 10 queued trades
+        Then Alice can claim 100 WCSPR
+        Then Bob can claim 39.8 WCSPR
+        When Admin sweeps the market to Charlie
+        Then the last call succeeds
+        Then MarketContract has 0 WCSPR
+        Then Charlie has 399.595 WCSPR
+        Then the insurance fund holds 0 WCSPR
+        Then the keeper pool holds 0 WCSPR
+        Then the accounting deficit is 0 WCSPR
+        Then Alice can claim 0 WCSPR
+        When Alice claims
+        Then the last call fails with NothingToClaim
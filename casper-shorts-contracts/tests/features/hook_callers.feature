@@ -0,0 +1,53 @@
+Feature: Token hooks can only be called by their token contract
+
+    Scenario Outline: <caller> can't deposit Bob's WCSPR through <hook>
+        When <caller> calls <hook> on behalf of Bob with 100 tokens
+        Then the last call fails with <error>
+        Then Bob has 1000 WCSPR
+        Then Bob has 0 LONG
+        Then Bob has 0 SHORT
+
+        Examples:
+            | caller       | hook               | error                                      |
+            | Admin        | deposit_long_from  | WCSPRTokenContractNotACallerOnLongDeposit  |
+            | Alice        | deposit_long_from  | WCSPRTokenContractNotACallerOnLongDeposit  |
+            | Bob          | deposit_long_from  | WCSPRTokenContractNotACallerOnLongDeposit  |
+            | Charlie      | deposit_long_from  | WCSPRTokenContractNotACallerOnLongDeposit  |
+            | FeeCollector | deposit_long_from  | WCSPRTokenContractNotACallerOnLongDeposit  |
+            | Admin        | deposit_short_from | WCSPRTokenContractNotACallerOnShortDeposit |
+            | Alice        | deposit_short_from | WCSPRTokenContractNotACallerOnShortDeposit |
+            | Bob          | deposit_short_from | WCSPRTokenContractNotACallerOnShortDeposit |
+            | Charlie      | deposit_short_from | WCSPRTokenContractNotACallerOnShortDeposit |
+            | FeeCollector | deposit_short_from | WCSPRTokenContractNotACallerOnShortDeposit |
+
+    Scenario Outline: <caller> can't withdraw Bob's tokens through <hook>
+        When Bob goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When <caller> calls <hook> on behalf of Bob with 50 tokens
+        Then the last call fails with <error>
+        Then Bob has 800 WCSPR
+        Then Bob has 99.5 LONG
+        Then Bob has 99.5 SHORT
+
+        Examples:
+            | caller       | hook                | error                                    |
+            | Admin        | withdraw_long_from  | LongTokenContractNotACallerOnWithdrawal  |
+            | Alice        | withdraw_long_from  | LongTokenContractNotACallerOnWithdrawal  |
+            | Bob          | withdraw_long_from  | LongTokenContractNotACallerOnWithdrawal  |
+            | Charlie      | withdraw_long_from  | LongTokenContractNotACallerOnWithdrawal  |
+            | FeeCollector | withdraw_long_from  | LongTokenContractNotACallerOnWithdrawal  |
+            | Admin        | withdraw_short_from | ShortTokenContractNotACallerOnWithdrawal |
+            | Alice        | withdraw_short_from | ShortTokenContractNotACallerOnWithdrawal |
+            | Bob          | withdraw_short_from | ShortTokenContractNotACallerOnWithdrawal |
+            | Charlie      | withdraw_short_from | ShortTokenContractNotACallerOnWithdrawal |
+            | FeeCollector | withdraw_short_from | ShortTokenContractNotACallerOnWithdrawal |
+
+    Scenario: The token contracts still call the hooks
+        When Bob transfers 100 WCSPR to LongContract
+        When Bob transfers 100 WCSPR to ShortContract
+        Then Bob has 99.5 LONG
+        Then Bob has 99.5 SHORT
+        When Bob transfers 50 LONG to WCSPRContract
+        When Bob transfers 50 SHORT to WCSPRContract
+        Then Bob has 49.5 LONG
+        Then Bob has 49.5 SHORT
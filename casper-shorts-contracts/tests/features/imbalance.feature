@@ -0,0 +1,40 @@
+Feature: Imbalance guard
+
+    Scenario: Guard is skipped while the other side is empty
+        Given the liquidity of a side can be at most 20000 basis points of the other side
+        Then the LONG side can accept any amount
+        When Alice goes long with 300 WCSPR
+        Then the last call succeeds
+        Then the LONG side can accept any amount
+        Then the SHORT side can accept 600 WCSPR
+
+    Scenario: Deposits skewing the pool revert
+        Given the liquidity of a side can be at most 20000 basis points of the other side
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+        Then the LONG side can accept 0 WCSPR
+        Then the SHORT side can accept 500 WCSPR
+        When Alice goes long with 10 WCSPR for at least 0 LONG until 1000
+        Then the last call fails with ImbalanceExceeded
+        When Bob goes short with 600 WCSPR for at least 0 SHORT until 1000
+        Then the last call fails with ImbalanceExceeded
+        When Bob goes short with 500 WCSPR for at least 0 SHORT until 1000
+        Then the last call succeeds
+        Then the SHORT side can accept 0 WCSPR
+
+    Scenario: Deposits skewing the pool are partially filled
+        Given the liquidity of a side can be at most 20000 basis points of the other side with partial fills
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 200 WCSPR
+        Then the LONG side can accept 100 WCSPR
+        When Alice goes long with 150 WCSPR for at least 0 LONG until 1000
+        Then the last call succeeds
+        Then Alice has 600 WCSPR
+        Then Alice has 398 LONG
+        Then the LONG side can accept 0 WCSPR
+        When Alice goes long with 10 WCSPR for at least 0 LONG until 1000
+        Then the last call fails with ImbalanceExceeded
+
+    Scenario: Ratio below one is rejected
+        Given the liquidity of a side can be at most 5000 basis points of the other side
+        Then the last call fails with InvalidImbalanceRatio
@@ -0,0 +1,45 @@
+Feature: Insurance fund
+
+    Background:
+        Given the insurance fund gets 10000 basis points of fees
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+
+    Scenario: The fund is paid from the fees
+        Then the insurance fund holds 1 WCSPR
+        Then FeeCollector has 0 WCSPR
+        Then MarketContract has 200 WCSPR
+        Then the accounting deficit is 0 WCSPR
+
+    Scenario: The fund tops up the winning side when the move is capped
+        When price changes to 0.004 USD
+        Then the insurance fund holds 0 WCSPR
+        Then the insurance fund paid 1 WCSPR for a capped move
+        Then the position of Alice is 99.5 LONG worth 0 WCSPR
+        Then the position of Bob is 99.5 SHORT worth 199 WCSPR
+        Then the accounting deficit is 0 WCSPR
+
+    Scenario: Uncapped moves don't touch the fund
+        When price changes to 0.008 USD
+        Then the insurance fund holds 1 WCSPR
+        Then the position of Bob is 99.5 SHORT worth 123.753125 WCSPR
+
+    Scenario: The fund covers accounting deficits
+        When the market loses 0.4 WCSPR
+        Then the accounting deficit is 0.4 WCSPR
+        When Charlie covers the accounting deficit
+        Then the accounting deficit is 0 WCSPR
+        Then the insurance fund holds 0.6 WCSPR
+        Then the insurance fund paid 0.4 WCSPR for a deficit
+
+    Scenario: Deficits larger than the fund are covered partially
+        When the market loses 1.5 WCSPR
+        When Charlie covers the accounting deficit
+        Then the insurance fund holds 0 WCSPR
+        Then the accounting deficit is 0.5 WCSPR
+
+    Scenario: Only the owner sets a valid fund share
+        When Alice sets the insurance fund share to 5000 basis points
+        Then the last call fails with CallerNotTheOwner
+        When Admin sets the insurance fund share to 10001 basis points
+        Then the last call fails with InsuranceFeeShareTooHigh
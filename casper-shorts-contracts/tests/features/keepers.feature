@@ -0,0 +1,62 @@
+Feature: Keeper rewards
+
+    Background:
+        Given keepers get 5000 basis points of fees, 0.1 WCSPR per price update and 0.2 WCSPR per order, up to 0.25 WCSPR every 100 milliseconds
+        Given Charlie is a keeper
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+
+    Scenario: Keepers share the fees
+        Then the keeper pool holds 1 WCSPR
+        Then FeeCollector has 1 WCSPR
+
+    Scenario: Keepers are rewarded for fresh prices up to the round cap
+        When Charlie submits price 0.011 USD at 90
+        Then the last call succeeds
+        Then Charlie earned 0.1 WCSPR as a keeper
+        When Charlie submits price 0.011 USD at 95
+        When Charlie submits price 0.011 USD at 100
+        Then Charlie earned 0.25 WCSPR as a keeper
+        Then Charlie has 0.25 WCSPR
+
+        When Charlie submits price 0.012 USD at 100
+        Then the last call succeeds
+        Then price is 0.012 USD
+        Then Charlie earned 0.25 WCSPR as a keeper
+
+        When the block time is 200
+        When Charlie submits price 0.012 USD at 200
+        Then Charlie earned 0.35 WCSPR as a keeper
+        Then the keeper pool holds 0.65 WCSPR
+
+    Scenario: Only fresh prices from keepers are accepted
+        When Charlie submits price 0.011 USD at 90
+        When Charlie submits price 0.011 USD at 50
+        Then the last call fails with NewPriceIsTooOld
+        When Charlie submits price 0.011 USD at 300
+        Then the last call fails with NewPriceIsFromTheFuture
+        When Bob submits price 0.011 USD at 100
+        Then the last call fails with CallerNotTheOwner
+        Then Charlie earned 0.1 WCSPR as a keeper
+
+    Scenario: Anyone executing orders is rewarded by the order's owner
+        When price changes to 0.008 USD
+        When Alice orders to close 100 LONG when the price falls below 0.009 USD
+        When Bob executes at most 10 orders
+        Then order 1 is executed
+        Then Bob earned 0.2 WCSPR as a keeper
+        Then the keeper pool holds 1.1875 WCSPR
+        Then Alice has 774.425 WCSPR
+
+    Scenario: Executing one's own orders earns nothing
+        When price changes to 0.008 USD
+        When Alice orders to close 100 LONG when the price falls below 0.009 USD
+        When Alice executes at most 10 orders
+        Then Alice earned 0.2 WCSPR as a keeper
+        Then Alice has 774.625 WCSPR
+
+    Scenario: The keepers' fee share can't exceed the fees
+        When the keepers' fee share is set to 10001 basis points
+        Then the last call fails with KeeperFeeShareTooHigh
+        When the keepers' fee share is set to 10000 basis points
+        Then the last call succeeds
@@ -0,0 +1,46 @@
+Feature: Market limits
+
+    Scenario: Deposits below the minimum are rejected
+        Given the minimum deposit is 10 WCSPR
+        When Alice goes long with 5 WCSPR for at least 0 LONG until 1000
+        Then the last call fails with DepositTooSmall
+        When Alice goes long with 10 WCSPR for at least 0 LONG until 1000
+        Then the last call succeeds
+        Then Alice has 9.95 LONG
+
+    Scenario: Withdrawals below the minimum are rejected unless closing the position
+        Given the minimum withdrawal is 10 tokens
+        When Alice goes long with 100 WCSPR
+        When Alice redeems 5 LONG for at least 0 WCSPR until 1000
+        Then the last call fails with WithdrawTooSmall
+        When Alice orders to close 5 LONG when the price falls below 0.008 USD
+        Then the last call fails with WithdrawTooSmall
+        When Alice redeems 94.5 LONG for at least 0 WCSPR until 1000
+        Then the last call succeeds
+        When Alice redeems 5 LONG for at least 0 WCSPR until 1000
+        Then the last call succeeds
+        Then Alice has 0 LONG
+
+    Scenario: Side liquidity is capped
+        Given the liquidity of each side is capped at 500 WCSPR
+        When Alice goes long with 300 WCSPR
+        When Bob goes long with 300 WCSPR for at least 0 LONG until 1000
+        Then the last call fails with SideLiquidityCapExceeded
+        When Bob goes long with 200 WCSPR for at least 0 LONG until 1000
+        Then the last call succeeds
+        When Bob goes short with 300 WCSPR for at least 0 SHORT until 1000
+        Then the last call succeeds
+
+    Scenario: Account share of a side is capped
+        Given an account can hold at most 6000 basis points of a side holding 100 WCSPR or more
+        When Alice goes long with 50 WCSPR
+        When Bob goes long with 100 WCSPR for at least 0 LONG until 1000
+        Then the last call fails with AccountShareExceeded
+        When Bob goes long with 60 WCSPR for at least 0 LONG until 1000
+        Then the last call succeeds
+        When Alice goes long with 50 WCSPR for at least 0 LONG until 1000
+        Then the last call fails with AccountShareExceeded
+        When Alice attempts to transfer 10 LONG to Bob
+        Then the last call fails with AccountShareExceeded
+        When Alice attempts to transfer 5 LONG to Bob
+        Then the last call succeeds
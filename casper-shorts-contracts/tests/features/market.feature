@@ -45,4 +45,22 @@ Feature: Casper Shorts MarketContract tests
     Scenario: Price update on empty pool
         When price changes to 0.03 USD
         Then price is 0.03 USD
-    
\ No newline at end of file
+
+    Scenario: Staking rewards are shared by both sides
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+        When staking rewards of 4 WCSPR are distributed
+        Then MarketContract has 402 WCSPR
+        Then FeeCollector has 2 WCSPR
+
+        When Alice withdraws 298.5 LONG
+        Then Alice has 999.9925 WCSPR
+        When Bob withdraws 99.5 SHORT
+        Then Bob has 999.9975 WCSPR
+
+    Scenario: Staking rewards sent to the treasury
+        When Alice goes long with 300 WCSPR
+        When staking rewards are sent to the treasury
+        When staking rewards of 4 WCSPR are distributed
+        Then MarketContract has 298.5 WCSPR
+        Then FeeCollector has 5.5 WCSPR
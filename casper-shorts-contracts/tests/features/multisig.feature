@@ -0,0 +1,59 @@
+Feature: Multisig admin
+
+    Scenario: Privileged Market calls need enough approvals
+        Given a multisig of Alice, Bob and Charlie requiring 2 approvals owns the contracts
+        When the flip fee is set to 30 basis points
+        Then the last call fails with CallerNotTheOwner
+        When Alice proposes setting the flip fee to 30 basis points through the multisig
+        Then the last call succeeds
+        Then 1 signer approved multisig proposal 1
+        When Alice executes multisig proposal 1
+        Then the last call fails with NotEnoughApprovals
+        When Alice approves multisig proposal 1
+        Then the last call fails with AlreadyApproved
+        When FeeCollector approves multisig proposal 1
+        Then the last call fails with NotASigner
+        When Bob approves multisig proposal 1
+        Then 2 signers approved multisig proposal 1
+        When Charlie executes multisig proposal 1
+        Then the last call succeeds
+        Then the flip fee is 30 basis points
+        When Bob executes multisig proposal 1
+        Then the last call fails with ProposalAlreadyExecuted
+
+    Scenario: Revoked approvals don't count
+        Given a multisig of Alice, Bob and Charlie requiring 2 approvals owns the contracts
+        When Alice proposes setting the flip fee to 30 basis points through the multisig
+        When Bob approves multisig proposal 1
+        When Bob revokes the approval of multisig proposal 1
+        Then 1 signer approved multisig proposal 1
+        When Bob revokes the approval of multisig proposal 1
+        Then the last call fails with NotApproved
+        When Alice executes multisig proposal 1
+        Then the last call fails with NotEnoughApprovals
+        Then the flip fee is 50 basis points
+
+    Scenario: Token calls go through the multisig
+        Given a multisig of Alice and Bob requiring 1 approval owns the contracts
+        When Bob proposes setting the WCSPR config delay to 259200000 through the multisig
+        When Bob executes multisig proposal 1
+        Then the last call succeeds
+        Then the WCSPR config delay is 259200000
+
+    Scenario: Signers are replaced through a proposal
+        Given a multisig of Alice, Bob and Charlie requiring 2 approvals owns the contracts
+        When Alice replaces the signers with Alice and Bob requiring 1 approval directly
+        Then the last call fails with CallerNotMultisig
+        When Alice proposes replacing the signers with Alice and Bob requiring 1 approval
+        When Charlie approves multisig proposal 1
+        When Charlie executes multisig proposal 1
+        Then the last call succeeds
+        Then 2 accounts sign for the multisig
+        When Charlie proposes setting the flip fee to 30 basis points through the multisig
+        Then the last call fails with NotASigner
+
+    Scenario: The threshold must be reachable
+        Given a multisig of Alice and Bob requiring 3 approvals owns the contracts
+        Then the last call fails with InvalidThreshold
+        Given a multisig of Alice and Alice requiring 1 approval owns the contracts
+        Then the last call fails with DuplicateSigner
@@ -0,0 +1,84 @@
+Feature: Stop-loss and take-profit orders
+
+    Background:
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+
+    Scenario: Stop-loss executes when the price falls
+        When Alice orders to close 100 LONG when the price falls below 0.008 USD
+        Then the last call succeeds
+        Then Alice has 198.5 LONG
+        Then 1 order of Alice is open
+        Then the position of Alice is 298.5 LONG worth 297.0075 WCSPR
+
+        When price changes to 0.009 USD
+        Then order 1 is open
+
+        When price changes to 0.008 USD
+        Then order 1 is executed
+        Then 0 orders of Alice are open
+        Then Alice has 198.5 LONG
+        Then Alice has 777.388888889 WCSPR
+
+    Scenario: Take-profit executes when the price rises
+        When Alice orders to close 100 LONG when the price rises above 0.012 USD
+        When price changes to 0.012 USD
+        Then order 1 is executed
+        Then Alice has 806.133333333 WCSPR
+        Then the realized LONG profit of Alice is 5.630820833 WCSPR
+
+    Scenario: The execution reward counts against the realized profit
+        Given keepers get 0 basis points of fees, 0 WCSPR per price update and 2 WCSPR per order, up to 10 WCSPR every 100 milliseconds
+        When Alice orders to close 100 LONG when the price rises above 0.012 USD
+        When price changes to 0.012 USD
+        Then order 1 is executed
+        Then Alice has 804.133333333 WCSPR
+        Then the realized LONG profit of Alice is 3.630820833 WCSPR
+
+    Scenario: Orders can be cancelled by the owner only
+        When Bob orders to close 50 SHORT when the price rises above 0.012 USD
+        When Alice cancels order 1
+        Then the last call fails with NotOrderOwner
+        When Bob cancels order 1
+        Then the last call succeeds
+        Then order 1 is cancelled
+        Then Bob has 99.5 SHORT
+        When Bob cancels order 1
+        Then the last call fails with OrderNotOpen
+
+        When price changes to 0.012 USD
+        Then Bob has 99.5 SHORT
+
+    Scenario: Anyone executes orders triggered before they were placed
+        When price changes to 0.008 USD
+        When Alice orders to close 100 LONG when the price falls below 0.009 USD
+        Then order 1 is open
+
+        When Charlie executes at most 10 orders
+        Then the last call succeeds
+        Then order 1 is executed
+        Then Alice has 774.625 WCSPR
+
+    Scenario: Orders execute in the order of their trigger prices
+        When Alice orders to close 50 LONG when the price falls below 0.008 USD
+        When Alice orders to close 50 LONG when the price falls below 0.009 USD
+        When Alice orders to close 50 LONG when the price rises above 0.012 USD
+        When price changes to 0.0085 USD
+        Then order 1 is open
+        Then order 2 is executed
+        Then order 3 is open
+
+    Scenario: Dust orders are rejected
+        When Alice orders to close 0.5 LONG when the price falls below 0.008 USD
+        Then the last call fails with OrderTooSmall
+        When Alice orders to close 1.1 LONG when the price falls below 0.008 USD
+        Then the last call succeeds
+
+    Scenario: Open orders of an account are capped
+        When Alice places 10 orders to close 10 LONG when the price falls below 0.008 USD
+        Then 10 orders of Alice are open
+        When Alice orders to close 10 LONG when the price falls below 0.008 USD
+        Then the last call fails with TooManyOpenOrders
+        When Alice cancels order 1
+        When Alice orders to close 10 LONG when the price falls below 0.008 USD
+        Then the last call succeeds
@@ -0,0 +1,45 @@
+Feature: Emergency pause
+
+    Background:
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+
+    Scenario: A paused market takes no deposits, withdrawals or flips
+        When Admin pauses the market
+        Then the market is paused
+        When Alice goes long for Alice with 100 WCSPR
+        Then the last call fails with MarketPaused
+        When Alice redeems 99.5 LONG to Alice
+        Then the last call fails with MarketPaused
+        When Alice flips 99.5 LONG
+        Then the last call fails with MarketPaused
+        Then Alice has 99.5 LONG
+        Then Alice has 900 WCSPR
+
+    Scenario: Trading resumes after the unpause
+        When Admin pauses the market
+        When Admin unpauses the market
+        Then the market is not paused
+        When Alice redeems 99.5 LONG to Alice
+        Then the last call succeeds
+        Then Alice has 999.0025 WCSPR
+
+    Scenario: Only the owner pauses and unpauses
+        When Alice pauses the market
+        Then the last call fails with CallerNotTheOwner
+        When Admin pauses the market
+        When Alice unpauses the market
+        Then the last call fails with CallerNotTheOwner
+        Then the market is paused
+
+    Scenario: A multisig owner pauses through a proposal
+        Given a multisig of Alice, Bob and Charlie requiring 2 approvals owns the contracts
+        When Admin pauses the market
+        Then the last call fails with CallerNotTheOwner
+        When Alice proposes pausing the market through the multisig
+        When Bob approves multisig proposal 1
+        When Charlie executes multisig proposal 1
+        Then the last call succeeds
+        Then the market is paused
+        When Bob flips 99.5 SHORT
+        Then the last call fails with MarketPaused
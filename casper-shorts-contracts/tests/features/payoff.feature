@@ -0,0 +1,30 @@
+Feature: Payoff models
+
+    Scenario: Markets use the linear payoff by default
+        Then the market uses the linear payoff
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        Then the position of Bob is 99.5 SHORT worth 79.202 WCSPR
+
+    Scenario: A leveraged payoff moves a multiple of the linear one
+        Given a market with a 2x leveraged payoff
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        Then the position of Bob is 99.5 SHORT worth 59.4015 WCSPR
+        Then the position of Alice is 99.5 LONG worth 138.6035 WCSPR
+
+    Scenario: A capped payoff limits the gain and the loss
+        Given a market with a payoff capped at 1000 basis points of gain and 1500 of loss
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        Then the position of Bob is 99.5 SHORT worth 89.10225 WCSPR
+
+    Scenario: A power payoff follows the squared price move
+        Given a market with a power payoff
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        Then the position of Bob is 99.5 SHORT worth 55.4414 WCSPR
@@ -0,0 +1,33 @@
+Feature: Profit and loss
+
+    Scenario: Empty position has no PnL
+        Then the LONG entry price of Alice is 0 WCSPR
+        Then the unrealized LONG profit of Alice is 0 WCSPR
+        Then the realized LONG profit of Alice is 0 WCSPR
+
+    Scenario: Unrealized and realized PnL follow the price
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+        Then the LONG entry price of Alice is 1.005025125 WCSPR
+        Then the SHORT entry price of Bob is 1.005025125 WCSPR
+        Then the unrealized LONG loss of Alice is 2.9925 WCSPR
+
+        When price changes to 0.012 USD
+        Then the unrealized LONG profit of Alice is 16.808 WCSPR
+        Then the unrealized SHORT loss of Bob is 20.798 WCSPR
+
+        When Alice redeems 100 LONG for at least 0 WCSPR until 1000
+        Then the realized LONG profit of Alice is 5.630820833 WCSPR
+        Then the LONG entry price of Alice is 1.005025125 WCSPR
+        Then the unrealized LONG profit of Alice is 11.177179167 WCSPR
+
+    Scenario: Transferred tokens keep the sender's cost basis
+        When Alice goes long with 300 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.012 USD
+        When Bob goes long with 100 WCSPR
+        Then the LONG entry price of Bob is 1.072026801 WCSPR
+
+        When Alice transfers 93.28125 LONG to Bob
+        Then the LONG entry price of Bob is 1.038525963 WCSPR
+        Then the LONG entry price of Alice is 1.005025125 WCSPR
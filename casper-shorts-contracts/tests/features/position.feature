@@ -0,0 +1,23 @@
+Feature: Account positions
+
+    Scenario: Empty position
+        Then the position of Alice is 0 LONG worth 0 WCSPR
+        Then the position of Alice is 0 SHORT worth 0 WCSPR
+        Then Alice owns 0 basis points of the LONG pool
+        Then LONG share price is 1 WCSPR
+
+    Scenario: Position follows the price
+        When Alice goes long with 300 WCSPR
+        When Bob goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        Then the position of Alice is 298.5 LONG worth 297.0075 WCSPR
+        Then Alice owns 7500 basis points of the LONG pool
+        Then Bob owns 2500 basis points of the LONG pool
+        Then Bob owns 10000 basis points of the SHORT pool
+        Then LONG share price is 1 WCSPR
+
+        When price changes to 0.012 USD
+        Then LONG share price is 1.05 WCSPR
+        Then SHORT share price is 0.8 WCSPR
+        Then the position of Alice is 298.5 LONG worth 311.857875 WCSPR
+        Then the position of Bob is 99.5 SHORT worth 79.202 WCSPR
@@ -0,0 +1,21 @@
+Feature: Trade previews
+
+    Scenario: Deposit preview matches the deposit
+        When Alice goes long with 300 WCSPR
+        Then depositing 100 WCSPR into LONG previews 99.5 tokens and 0.5 WCSPR fee
+        Then depositing 100 WCSPR into SHORT previews 99.5 tokens and 0.5 WCSPR fee
+        Then MarketContract has 298.5 WCSPR
+
+        When Bob goes long with 100 WCSPR
+        Then Bob has 99.5 LONG
+        Then FeeCollector has 2 WCSPR
+
+    Scenario: Withdraw preview matches the withdrawal after a price change
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.008 USD
+        Then withdrawing 99.5 LONG of Alice previews 74.251875 WCSPR and 0.373125 WCSPR fee
+        Then Alice has 99.5 LONG
+
+        When Alice withdraws 99.5 LONG
+        Then Alice has 974.251875 WCSPR
@@ -0,0 +1,39 @@
+Feature: Deposits and withdrawals for another recipient
+
+    Scenario: Payer funds a position of the recipient
+        When Alice goes long for Bob with 100 WCSPR
+        Then the last call succeeds
+        Then Alice has 900 WCSPR
+        Then Alice has 0 LONG
+        Then Bob has 99.5 LONG
+        Then Bob has 1000 WCSPR
+        Then the LONG entry price of Bob is 1.005025125 WCSPR
+
+        When Alice goes short for Charlie with 100 WCSPR
+        Then Charlie has 99.5 SHORT
+        Then Alice has 800 WCSPR
+
+    Scenario: Holder redeems to a different recipient
+        When Bob goes long with 100 WCSPR
+        When Bob redeems 99.5 LONG to Charlie
+        Then the last call succeeds
+        Then Bob has 0 LONG
+        Then Bob has 900 WCSPR
+        Then Charlie has 99.0025 WCSPR
+        Then the realized LONG loss of Bob is 0.9975 WCSPR
+
+    Scenario: Tokens of others can't be redeemed
+        When Bob goes long with 100 WCSPR
+        When Alice redeems 99.5 LONG to Alice
+        Then the last call fails with InsufficientBalance
+        Then Bob has 99.5 LONG
+
+    Scenario: Contracts of the system can't be recipients
+        When Alice goes long for LongContract with 100 WCSPR
+        Then the last call fails with InvalidRecipient
+        When Alice goes short for MarketContract with 100 WCSPR
+        Then the last call fails with InvalidRecipient
+        When Bob goes short with 100 WCSPR
+        When Bob redeems 50 SHORT to WCSPRContract
+        Then the last call fails with InvalidRecipient
+        Then Alice has 1000 WCSPR
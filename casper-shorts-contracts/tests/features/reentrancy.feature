@@ -0,0 +1,43 @@
+Feature: Reentrancy protection
+
+    Background:
+        Given a market wired to reentrant tokens
+
+    Scenario: Harmless tokens work with the guard
+        When Alice goes long with 100 WCSPR on the reentrant market
+        Then the last call succeeds
+        Then Alice holds 99.5 LONG of the reentrant market
+        When the reentrant market redeems 50 LONG of Alice
+        Then the last call succeeds
+        Then Alice holds 49.5 LONG of the reentrant market
+
+    Scenario Outline: The <token> token can't re-enter <entry_point> while going long
+        Given the reentrant <token> token calls <entry_point> when called
+        When Alice goes long with 100 WCSPR on the reentrant market
+        Then the last call fails with ReentrantCall
+        Then Alice holds 1000 WCSPR of the reentrant market
+        Then Alice holds 0 LONG of the reentrant market
+
+        Examples:
+            | token | entry_point       |
+            | WCSPR | deposit_long      |
+            | WCSPR | deposit_long_from |
+            | WCSPR | withdraw_long     |
+            | LONG  | deposit_long      |
+            | LONG  | deposit_long_from |
+            | LONG  | on_token_transfer |
+
+    Scenario Outline: The <token> token can't re-enter <entry_point> while withdrawing
+        When Alice goes long with 100 WCSPR on the reentrant market
+        Given the reentrant <token> token calls <entry_point> when called
+        When the reentrant market redeems 50 LONG of Alice
+        Then the last call fails with ReentrantCall
+        Then Alice holds 99.5 LONG of the reentrant market
+
+        Examples:
+            | token | entry_point       |
+            | LONG  | withdraw_long     |
+            | LONG  | deposit_long      |
+            | LONG  | on_token_transfer |
+            | WCSPR | withdraw_long     |
+            | WCSPR | deposit_long_from |
@@ -0,0 +1,51 @@
+Feature: Market registry
+
+    Scenario: A wired market is registered once
+        Given Charlie is a keeper
+        When Admin registers the market with Charlie as the price feed
+        Then the last call succeeds
+        Then the registry lists 1 market
+        When Admin registers the market with Charlie as the price feed
+        Then the last call fails with MarketAlreadyRegistered
+        Then the registry lists 1 market
+
+    Scenario: The price feed must be a keeper of the market
+        When Admin registers the market with Charlie as the price feed
+        Then the last call fails with PriceFeedNotKeeper
+        Then the registry lists 0 markets
+
+    Scenario: Half-configured markets are rejected
+        When Admin registers a new unconfigured market
+        Then the last call fails with MarketNotConfigured
+        When Admin registers a new market configured only on the Market contract
+        Then the last call fails with LongTokenNotConfigured
+        Then the registry lists 0 markets
+
+    Scenario: Only the owner registers markets
+        Given Charlie is a keeper
+        When Alice registers the market with Charlie as the price feed
+        Then the last call fails with CallerNotTheOwner
+
+    Scenario: A market is registered only once the WCSPR token routes to it
+        Given the next market is deployed
+        When Admin registers the next market
+        Then the last call fails with WcsprNotRouted
+        When Alice proposes the next market on the WCSPR token
+        Then the last call fails with CallerNotTheOwner
+        When Admin proposes the next market on the WCSPR token
+        Then the last call succeeds
+        When half of the config delay passes
+        When Alice adds the next market to the WCSPR token
+        Then the last call fails with RouteNotReady
+        When the config delay passes
+        When Alice adds the next market to the WCSPR token
+        Then the last call succeeds
+        When Admin proposes the next market on the WCSPR token
+        Then the last call fails with AlreadyRouted
+        When Admin registers the next market
+        Then the last call succeeds
+        Then the registry lists 1 market
+        When Alice sends 100 WCSPR to the LONG token of the next market
+        Then the last call succeeds
+        Then the next market minted 99.5 LONG to Alice
+        Then Alice has 900 WCSPR
@@ -0,0 +1,37 @@
+Feature: Slippage and deadline protection
+
+    Scenario: Going long within the limits
+        When Alice goes long with 100 WCSPR for at least 99.5 LONG until 1000
+        Then the last call succeeds
+        Then Alice has 99.5 LONG
+        Then Alice has 900 WCSPR
+
+    Scenario: Going short with too few tokens out
+        When Alice goes short with 100 WCSPR for at least 99.6 SHORT until 1000
+        Then the last call fails with InsufficientTokensOut
+        Then Alice has 0 SHORT
+        Then Alice has 1000 WCSPR
+
+    Scenario: Going long after the deadline
+        When Alice goes long with 100 WCSPR for at least 0 LONG until 50
+        Then the last call fails with DeadlineExpired
+        Then Alice has 0 LONG
+        Then Alice has 1000 WCSPR
+
+    Scenario: Withdrawing after a price drop
+        When Alice goes long with 100 WCSPR
+        When Bob goes short with 100 WCSPR
+        When price changes to 0.008 USD
+        When Alice redeems 99.5 LONG for at least 99 WCSPR until 1000
+        Then the last call fails with InsufficientCollateralOut
+        Then Alice has 99.5 LONG
+
+        When Alice redeems 99.5 LONG for at least 74 WCSPR until 1000
+        Then the last call succeeds
+        Then Alice has 0 LONG
+
+    Scenario: Withdrawing after the deadline
+        When Bob goes short with 100 WCSPR
+        When Bob redeems 50 SHORT for at least 0 WCSPR until 99
+        Then the last call fails with DeadlineExpired
+        Then Bob has 99.5 SHORT
@@ -0,0 +1,18 @@
+Feature: Versioned state layout
+
+    Scenario: New contracts start at the current version
+        Then every contract is at state version 1
+        When Admin migrates the Market
+        Then the last call fails with StateAlreadyCurrent
+        When Admin migrates the LONG token
+        Then the last call fails with StateAlreadyCurrent
+        When Admin migrates the SHORT token
+        Then the last call fails with StateAlreadyCurrent
+        When Admin migrates the WCSPR token
+        Then the last call fails with StateAlreadyCurrent
+
+    Scenario: Only the owner migrates
+        When Alice migrates the Market
+        Then the last call fails with CallerNotTheOwner
+        When Alice migrates the LONG token
+        Then the last call fails with CallerNotTheOwner
@@ -0,0 +1,111 @@
+use casper_shorts_contracts::competition::MAX_PARTICIPANTS;
+use cucumber::{given, then, when};
+use odra::casper_types::U256;
+
+use crate::common::{
+    params::{Account, Amount},
+    world::CasperShortsWorld,
+};
+
+// Given a competition runs from 100 to 1000 with prizes of 50, 30 and 20 WCSPR
+#[given(regex = r"^a competition runs from (\d+) to (\d+) with prizes of (.+) WCSPR$")]
+fn start_competition(
+    world: &mut CasperShortsWorld,
+    start_time: u64,
+    end_time: u64,
+    prizes: String,
+) {
+    world.start_competition(
+        start_time,
+        end_time,
+        parse_prizes(&prizes),
+        MAX_PARTICIPANTS,
+    );
+}
+
+// Given a competition for 2 participants runs from 100 to 1000 with prizes of 50 WCSPR
+#[given(
+    regex = r"^a competition for (\d+) participants runs from (\d+) to (\d+) with prizes of (.+) WCSPR$"
+)]
+fn start_limited_competition(
+    world: &mut CasperShortsWorld,
+    max_participants: u32,
+    start_time: u64,
+    end_time: u64,
+    prizes: String,
+) {
+    world.start_competition(
+        start_time,
+        end_time,
+        parse_prizes(&prizes),
+        max_participants,
+    );
+}
+
+#[when(expr = "the block time is {int}")]
+fn set_block_time(world: &mut CasperShortsWorld, time: u64) {
+    let now = world.block_time();
+    assert!(time >= now, "Cannot go back in time");
+    world.advance_time(time - now);
+}
+
+#[when(expr = "{account} registers for the competition")]
+fn register(world: &mut CasperShortsWorld, account: Account) {
+    world.register_for_competition(account);
+}
+
+#[when("the competition is finalized")]
+fn finalize(world: &mut CasperShortsWorld) {
+    world.finalize_competition(Account::Admin);
+}
+
+#[when(expr = "{account} finalizes the competition")]
+fn finalize_as(world: &mut CasperShortsWorld, account: Account) {
+    world.finalize_competition(account);
+}
+
+#[then(expr = "{account} places {int} in the competition with a prize of {amount} WCSPR")]
+fn check_standing(world: &mut CasperShortsWorld, account: Account, place: usize, prize: Amount) {
+    let address = world.address(account);
+    let ranking = world.competition_ranking();
+    let standing = ranking
+        .get(place - 1)
+        .unwrap_or_else(|| panic!("Nobody placed {}", place));
+    assert_eq!(
+        standing.account, address,
+        "{:?} did not place {}",
+        account, place
+    );
+    assert_eq!(standing.prize, prize.value());
+}
+
+#[then(expr = "{account} finishes the competition with {amount} WCSPR")]
+fn check_final_balance(world: &mut CasperShortsWorld, account: Account, balance: Amount) {
+    let address = world.address(account);
+    let standing = world
+        .competition_ranking()
+        .into_iter()
+        .find(|standing| standing.account == address)
+        .unwrap_or_else(|| panic!("{:?} is not ranked", account));
+    let diff = standing.final_balance.abs_diff(balance.value());
+    assert!(
+        diff < U256::from(10_000),
+        "{:?} finished with {} WCSPR but expected {} WCSPR",
+        account,
+        Amount(standing.final_balance),
+        balance
+    );
+}
+
+#[then(expr = "{int} participants are ranked")]
+fn check_ranked_count(world: &mut CasperShortsWorld, count: usize) {
+    assert_eq!(world.competition_ranking().len(), count);
+}
+
+fn parse_prizes(prizes: &str) -> Vec<U256> {
+    prizes
+        .split(',')
+        .flat_map(|part| part.split(" and "))
+        .map(|prize| prize.trim().parse::<Amount>().unwrap().value())
+        .collect()
+}
@@ -0,0 +1,71 @@
+use cucumber::{then, when};
+
+use crate::common::{params::Account, world::CasperShortsWorld};
+
+#[when(expr = "{account} proposes {account} as the fee collector")]
+fn propose_fee_collector(world: &mut CasperShortsWorld, account: Account, fee_collector: Account) {
+    world.propose_fee_collector(account, fee_collector);
+}
+
+#[when(expr = "{account} proposes {account} as the market of the WCSPR token")]
+fn propose_wcspr_market(world: &mut CasperShortsWorld, account: Account, market: Account) {
+    world.propose_wcspr_market(account, market);
+}
+
+#[when(expr = "{account} executes the config change")]
+fn execute_config(world: &mut CasperShortsWorld, account: Account) {
+    world.execute_config(account);
+}
+
+#[when(expr = "{account} executes the WCSPR config change")]
+fn execute_wcspr_config(world: &mut CasperShortsWorld, account: Account) {
+    world.execute_wcspr_config(account);
+}
+
+#[when(expr = "{account} cancels the config change")]
+fn cancel_config(world: &mut CasperShortsWorld, account: Account) {
+    world.cancel_config(account);
+}
+
+#[when(expr = "{account} sets the config again")]
+fn reset_config(world: &mut CasperShortsWorld, account: Account) {
+    world.reset_config(account);
+}
+
+#[when(expr = "{account} sets the config delay to {int}")]
+fn set_config_delay(world: &mut CasperShortsWorld, account: Account, delay: u64) {
+    world.set_config_delay(account, delay);
+}
+
+#[when("half of the config delay passes")]
+fn half_config_delay_passes(world: &mut CasperShortsWorld) {
+    world.advance_time(world.config_delay() / 2);
+}
+
+#[when("the config delay passes")]
+fn config_delay_passes(world: &mut CasperShortsWorld) {
+    world.advance_time(world.config_delay());
+}
+
+#[then(expr = "the config delay is {int}")]
+fn check_config_delay(world: &mut CasperShortsWorld, delay: u64) {
+    assert_eq!(world.config_delay(), delay);
+}
+
+#[then("a config change is pending")]
+fn check_config_pending(world: &mut CasperShortsWorld) {
+    let eta = world
+        .pending_config_eta()
+        .expect("No pending config change");
+    assert_eq!(eta, world.block_time() + world.config_delay());
+}
+
+#[then("no config change is pending")]
+fn check_no_config_pending(world: &mut CasperShortsWorld) {
+    assert_eq!(world.pending_config_eta(), None);
+}
+
+#[then(expr = "the market emitted {word}")]
+fn check_market_event(world: &mut CasperShortsWorld, event: String) {
+    assert!(world.market_emitted(&event), "{} not emitted", event);
+}
@@ -0,0 +1,44 @@
+use casper_shorts_contracts::limits::EarlyExitFee;
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, TokenKind},
+    world::CasperShortsWorld,
+};
+
+#[when(expr = "{int} seconds pass")]
+fn advance_time(world: &mut CasperShortsWorld, seconds: u64) {
+    world.advance_time(seconds * 1000);
+}
+
+#[given(expr = "withdrawals within {int} seconds of a deposit pay an extra {int} basis points")]
+fn set_early_exit_fee(world: &mut CasperShortsWorld, seconds: u64, fee: u64) {
+    world.set_early_exit_fee(
+        Account::Admin,
+        EarlyExitFee {
+            holding_period: seconds * 1000,
+            fee,
+        },
+    );
+}
+
+#[when(expr = "{account} sets the early-exit fee to {int} basis points")]
+fn try_set_early_exit_fee(world: &mut CasperShortsWorld, account: Account, fee: u64) {
+    world.set_early_exit_fee(
+        account,
+        EarlyExitFee {
+            holding_period: 3_600_000,
+            fee,
+        },
+    );
+}
+
+#[then(expr = "{account} can exit {token_kind} without the early-exit fee from {int}")]
+fn check_exit_unlock_time(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    token: TokenKind,
+    time: u64,
+) {
+    assert_eq!(world.exit_unlock_time(account, token.side()), time);
+}
@@ -0,0 +1,49 @@
+use casper_shorts_contracts::{expiry::SWEEP_GRACE_PERIOD, system::Payoff};
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, Price},
+    world::CasperShortsWorld,
+};
+
+#[given(expr = "a market expiring at {int}")]
+fn dated_market(world: &mut CasperShortsWorld, expiry: u64) {
+    *world = CasperShortsWorld::with_market(Some(expiry), Payoff::Linear);
+}
+
+#[then(expr = "the market expires at {int}")]
+fn check_expiry(world: &mut CasperShortsWorld, expiry: u64) {
+    assert_eq!(world.market_expiry(), Some(expiry));
+}
+
+#[then(expr = "the market expired at {price} USD")]
+fn check_final_price(world: &mut CasperShortsWorld, price: Price) {
+    assert!(world.is_market_expired());
+    assert_eq!(world.final_price(), Some(price.value()));
+}
+
+#[then("the market is not expired")]
+fn check_not_expired(world: &mut CasperShortsWorld) {
+    assert!(!world.is_market_expired());
+    assert_eq!(world.final_price(), None);
+}
+
+#[then("the market is perpetual")]
+fn check_perpetual(world: &mut CasperShortsWorld) {
+    assert_eq!(world.market_expiry(), None);
+}
+
+#[when(expr = "{account} settles the market at {price} USD")]
+fn settle_market(world: &mut CasperShortsWorld, account: Account, price: Price) {
+    world.settle_market(account, price.value());
+}
+
+#[when(expr = "{account} sweeps the market to {account}")]
+fn sweep_market(world: &mut CasperShortsWorld, account: Account, recipient: Account) {
+    world.sweep_market(account, recipient);
+}
+
+#[when("the sweep grace period passes")]
+fn sweep_grace_period_passes(world: &mut CasperShortsWorld) {
+    world.advance_time(SWEEP_GRACE_PERIOD);
+}
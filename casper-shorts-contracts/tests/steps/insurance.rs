@@ -0,0 +1,51 @@
+use casper_shorts_contracts::insurance::PayoutReason;
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, Amount},
+    world::CasperShortsWorld,
+};
+
+#[given(expr = "the insurance fund gets {int} basis points of fees")]
+fn set_insurance_fee_share(world: &mut CasperShortsWorld, fee_share: u64) {
+    world.set_insurance_fee_share(Account::Admin, fee_share);
+}
+
+#[when(expr = "{account} sets the insurance fund share to {int} basis points")]
+fn try_set_insurance_fee_share(world: &mut CasperShortsWorld, account: Account, fee_share: u64) {
+    world.set_insurance_fee_share(account, fee_share);
+}
+
+#[when(expr = "the market loses {amount} WCSPR")]
+fn lose_market_wcspr(world: &mut CasperShortsWorld, amount: Amount) {
+    world.lose_market_wcspr(amount.value());
+}
+
+#[when(expr = "{account} covers the accounting deficit")]
+fn cover_deficit(world: &mut CasperShortsWorld, account: Account) {
+    world.cover_deficit(account);
+}
+
+#[then(expr = "the insurance fund holds {amount} WCSPR")]
+fn check_insurance_fund(world: &mut CasperShortsWorld, amount: Amount) {
+    assert_eq!(world.insurance_fund(), amount.value());
+}
+
+#[then(expr = "the accounting deficit is {amount} WCSPR")]
+fn check_accounting_deficit(world: &mut CasperShortsWorld, amount: Amount) {
+    assert_eq!(world.accounting_deficit(), amount.value());
+}
+
+#[then(regex = r"^the insurance fund paid (.+) WCSPR for (a capped move|a deficit)$")]
+fn check_insurance_payout(world: &mut CasperShortsWorld, amount: Amount, reason: String) {
+    let reason = match reason.as_str() {
+        "a capped move" => PayoutReason::CappedMove,
+        _ => PayoutReason::Deficit,
+    };
+    let paid: Vec<_> = world
+        .insurance_payouts(reason)
+        .iter()
+        .map(|payout| payout.amount)
+        .collect();
+    assert_eq!(paid, vec![amount.value()]);
+}
@@ -0,0 +1,56 @@
+use casper_shorts_contracts::keepers::KeeperConfig;
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, Amount, Price},
+    world::CasperShortsWorld,
+};
+
+#[given(
+    expr = "keepers get {int} basis points of fees, {amount} WCSPR per price update and {amount} WCSPR per order, up to {amount} WCSPR every {int} milliseconds"
+)]
+fn set_keeper_config(
+    world: &mut CasperShortsWorld,
+    fee_share: u64,
+    price_update_reward: Amount,
+    order_execution_reward: Amount,
+    max_rewards_per_round: Amount,
+    round_duration: u64,
+) {
+    world.set_keeper_config(KeeperConfig {
+        fee_share,
+        price_update_reward: price_update_reward.value(),
+        order_execution_reward: order_execution_reward.value(),
+        round_duration,
+        max_rewards_per_round: max_rewards_per_round.value(),
+    });
+}
+
+#[when(expr = "the keepers' fee share is set to {int} basis points")]
+fn set_keeper_fee_share(world: &mut CasperShortsWorld, fee_share: u64) {
+    let config = KeeperConfig {
+        fee_share,
+        ..world.keeper_config()
+    };
+    world.set_keeper_config(config);
+}
+
+#[given(expr = "{account} is a keeper")]
+fn set_keeper(world: &mut CasperShortsWorld, account: Account) {
+    world.set_keeper(account);
+}
+
+#[when(expr = "{account} submits price {price} USD at {int}")]
+fn submit_price(world: &mut CasperShortsWorld, account: Account, price: Price, timestamp: u64) {
+    world.submit_price(account, price.value(), timestamp);
+}
+
+#[then(expr = "{account} earned {amount} WCSPR as a keeper")]
+fn check_keeper_earnings(world: &mut CasperShortsWorld, account: Account, amount: Amount) {
+    assert_eq!(world.keeper_earnings(account), amount.value());
+}
+
+#[then(expr = "the keeper pool holds {amount} WCSPR")]
+fn check_keeper_pool(world: &mut CasperShortsWorld, amount: Amount) {
+    assert_eq!(world.keeper_pool(), amount.value());
+}
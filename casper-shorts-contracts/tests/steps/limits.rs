@@ -0,0 +1,56 @@
+use casper_shorts_contracts::limits::{ImbalanceGuard, ImbalanceMode};
+use cucumber::{given, then};
+use odra::casper_types::U256;
+
+use crate::common::{
+    params::{Amount, TokenKind},
+    world::CasperShortsWorld,
+};
+
+#[given(expr = "the liquidity of each side is capped at {amount} WCSPR")]
+fn cap_side_liquidity(world: &mut CasperShortsWorld, max: Amount) {
+    world.update_limits(|limits| limits.max_side_liquidity = max.value());
+}
+
+#[given(
+    expr = "an account can hold at most {int} basis points of a side holding {amount} WCSPR or more"
+)]
+fn cap_account_share(world: &mut CasperShortsWorld, max_share: u64, threshold: Amount) {
+    world.update_limits(|limits| {
+        limits.max_account_share = max_share;
+        limits.account_share_threshold = threshold.value();
+    });
+}
+
+#[given(expr = "the minimum deposit is {amount} WCSPR")]
+fn set_min_deposit(world: &mut CasperShortsWorld, min: Amount) {
+    world.update_limits(|limits| limits.min_deposit = min.value());
+}
+
+#[given(expr = "the minimum withdrawal is {amount} tokens")]
+fn set_min_withdraw(world: &mut CasperShortsWorld, min: Amount) {
+    world.update_limits(|limits| limits.min_withdraw = min.value());
+}
+
+// Given the liquidity of a side can be at most 20000 basis points of the other side
+#[given(
+    regex = r"^the liquidity of a side can be at most (\d+) basis points of the other side( with partial fills)?$"
+)]
+fn set_imbalance_guard(world: &mut CasperShortsWorld, max_ratio: u64, partial_fills: String) {
+    let mode = if partial_fills.is_empty() {
+        ImbalanceMode::Revert
+    } else {
+        ImbalanceMode::PartialFill
+    };
+    world.set_imbalance_guard(ImbalanceGuard { max_ratio, mode });
+}
+
+#[then(expr = "the {token_kind} side can accept {amount} WCSPR")]
+fn check_deposit_capacity(world: &mut CasperShortsWorld, token: TokenKind, capacity: Amount) {
+    assert_eq!(world.deposit_capacity(token.side()), capacity.value());
+}
+
+#[then(expr = "the {token_kind} side can accept any amount")]
+fn check_unlimited_capacity(world: &mut CasperShortsWorld, token: TokenKind) {
+    assert_eq!(world.deposit_capacity(token.side()), U256::MAX);
+}
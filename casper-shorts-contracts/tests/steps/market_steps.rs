@@ -1,7 +1,8 @@
+use casper_shorts_contracts::market::RewardsDestination;
 use cucumber::{then, when};
 
 use crate::common::{
-    params::{Account, Amount, Price, TokenKind},
+    params::{Account, Amount, ExpectedError, Price, TokenKind},
     world::CasperShortsWorld,
 };
 
@@ -51,3 +52,178 @@ fn transfer(
 ) {
     world.transfer(token, sender, amount.value(), receiver);
 }
+
+#[when(expr = "{account} goes long for {account} with {amount} WCSPR")]
+fn go_long_for(world: &mut CasperShortsWorld, payer: Account, recipient: Account, amount: Amount) {
+    world.go_long_for(payer, recipient, amount.value());
+}
+
+#[when(expr = "{account} goes short for {account} with {amount} WCSPR")]
+fn go_short_for(world: &mut CasperShortsWorld, payer: Account, recipient: Account, amount: Amount) {
+    world.go_short_for(payer, recipient, amount.value());
+}
+
+#[when(expr = "{account} redeems {amount} {token_kind} to {account}")]
+fn withdraw_to(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    amount: Amount,
+    token: TokenKind,
+    recipient: Account,
+) {
+    world.withdraw_to(account, token.side(), amount.value(), recipient);
+}
+
+#[when(expr = "{account} flips {amount} {token_kind}")]
+fn flip(world: &mut CasperShortsWorld, account: Account, tokens: Amount, token: TokenKind) {
+    world.flip(account, token.side(), tokens.value());
+}
+
+#[when(expr = "the flip fee is set to {int} basis points")]
+fn set_flip_fee(world: &mut CasperShortsWorld, fee: u64) {
+    world.set_flip_fee(fee);
+}
+
+#[when(expr = "{account} calls {word} on behalf of {account} with {amount} tokens")]
+fn call_hook(
+    world: &mut CasperShortsWorld,
+    caller: Account,
+    hook: String,
+    owner: Account,
+    amount: Amount,
+) {
+    world.call_hook(caller, &hook, owner, amount.value());
+}
+
+#[when(expr = "{account} attempts to transfer {amount} {token_kind} to {account}")]
+fn try_transfer(
+    world: &mut CasperShortsWorld,
+    sender: Account,
+    amount: Amount,
+    token: TokenKind,
+    receiver: Account,
+) {
+    world.try_transfer(token, sender, amount.value(), receiver);
+}
+
+#[when(expr = "staking rewards of {amount} WCSPR are distributed")]
+fn distribute_rewards(world: &mut CasperShortsWorld, amount: Amount) {
+    world.distribute_rewards(amount.value());
+}
+
+#[when("staking rewards are sent to the treasury")]
+fn send_rewards_to_treasury(world: &mut CasperShortsWorld) {
+    world.set_rewards_destination(RewardsDestination::Treasury);
+}
+
+#[when(expr = "{account} goes long with {amount} WCSPR for at least {amount} LONG until {int}")]
+fn go_long_with_slippage(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    amount: Amount,
+    min_tokens_out: Amount,
+    deadline: u64,
+) {
+    world.go_long_with_slippage(account, amount.value(), min_tokens_out.value(), deadline);
+}
+
+#[when(expr = "{account} goes short with {amount} WCSPR for at least {amount} SHORT until {int}")]
+fn go_short_with_slippage(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    amount: Amount,
+    min_tokens_out: Amount,
+    deadline: u64,
+) {
+    world.go_short_with_slippage(account, amount.value(), min_tokens_out.value(), deadline);
+}
+
+#[when(expr = "{account} redeems {amount} {token_kind} for at least {amount} WCSPR until {int}")]
+fn withdraw_with_slippage(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    amount: Amount,
+    token: TokenKind,
+    min_collateral_out: Amount,
+    deadline: u64,
+) {
+    let (amount, min_out) = (amount.value(), min_collateral_out.value());
+    match token {
+        TokenKind::LONG => world.withdraw_long_with_slippage(account, amount, min_out, deadline),
+        TokenKind::SHORT => world.withdraw_short_with_slippage(account, amount, min_out, deadline),
+        TokenKind::WCSPR => panic!("Cannot withdraw using WCSPR"),
+    }
+}
+
+#[then(expr = "the last call fails with {error}")]
+fn check_last_error(world: &mut CasperShortsWorld, error: ExpectedError) {
+    assert_eq!(world.last_error, Some(error.0));
+}
+
+#[then("the last call succeeds")]
+fn check_last_call_succeeded(world: &mut CasperShortsWorld) {
+    assert_eq!(world.last_error, None);
+}
+
+#[then(
+    expr = "depositing {amount} WCSPR into {token_kind} previews {amount} tokens and {amount} WCSPR fee"
+)]
+fn check_deposit_preview(
+    world: &mut CasperShortsWorld,
+    amount: Amount,
+    token: TokenKind,
+    tokens_out: Amount,
+    fee: Amount,
+) {
+    let preview = world.preview_deposit(token.side(), amount.value());
+    assert_eq!(preview.amount_out, tokens_out.value());
+    assert_eq!(preview.fee, fee.value());
+}
+
+#[then(
+    expr = "withdrawing {amount} {token_kind} of {account} previews {amount} WCSPR and {amount} WCSPR fee"
+)]
+fn check_withdraw_preview(
+    world: &mut CasperShortsWorld,
+    tokens: Amount,
+    token: TokenKind,
+    account: Account,
+    collateral_out: Amount,
+    fee: Amount,
+) {
+    let preview = world.preview_withdraw(account, token.side(), tokens.value());
+    assert_eq!(preview.amount_out, collateral_out.value());
+    assert_eq!(preview.fee, fee.value());
+}
+
+#[then(
+    expr = "flipping {amount} {token_kind} of {account} previews {amount} tokens and {amount} WCSPR fee"
+)]
+fn check_flip_preview(
+    world: &mut CasperShortsWorld,
+    tokens: Amount,
+    token: TokenKind,
+    account: Account,
+    tokens_out: Amount,
+    fee: Amount,
+) {
+    let preview = world.preview_flip(account, token.side(), tokens.value());
+    assert_eq!(preview.amount_out, tokens_out.value());
+    assert_eq!(preview.fee, fee.value());
+}
+
+#[then(
+    expr = "{token_kind} previews of {account} for {amount} tokens include {amount} WCSPR early-exit fee"
+)]
+fn check_early_exit_fee_preview(
+    world: &mut CasperShortsWorld,
+    token: TokenKind,
+    account: Account,
+    tokens: Amount,
+    early_exit_fee: Amount,
+) {
+    let withdraw = world.preview_withdraw(account, token.side(), tokens.value());
+    assert_eq!(withdraw.early_exit_fee, early_exit_fee.value());
+    let flip = world.preview_flip(account, token.side(), tokens.value());
+    assert_eq!(flip.early_exit_fee, early_exit_fee.value());
+}
@@ -1,2 +1,19 @@
 pub mod balances;
+pub mod competition;
+pub mod config_timelock;
+pub mod early_exit;
+pub mod expiry;
+pub mod insurance;
+pub mod keepers;
+pub mod limits;
 pub mod market_steps;
+pub mod multisig;
+pub mod orders;
+pub mod pause;
+pub mod payoff;
+pub mod pnl;
+pub mod positions;
+pub mod reentrancy;
+pub mod registry;
+pub mod settlement;
+pub mod version;
@@ -0,0 +1,101 @@
+use cucumber::{given, then, when};
+use odra::{
+    casper_types::{runtime_args, RuntimeArgs},
+    prelude::*,
+};
+
+use crate::common::{params::Account, world::CasperShortsWorld};
+
+#[given(regex = r"^a multisig of (.+) requiring (\d+) approvals? owns the contracts$")]
+fn install_multisig(world: &mut CasperShortsWorld, signers: String, threshold: u32) {
+    world.install_multisig(accounts(&signers), threshold);
+}
+
+#[when(expr = "{account} proposes setting the flip fee to {int} basis points through the multisig")]
+fn propose_flip_fee(world: &mut CasperShortsWorld, account: Account, fee: u64) {
+    world.propose_market_call(account, "set_flip_fee", runtime_args! { "fee" => fee });
+}
+
+#[when(expr = "{account} proposes pausing the market through the multisig")]
+fn propose_pause(world: &mut CasperShortsWorld, account: Account) {
+    world.propose_market_call(account, "pause", runtime_args! {});
+}
+
+#[when(expr = "{account} proposes setting the WCSPR config delay to {int} through the multisig")]
+fn propose_wcspr_config_delay(world: &mut CasperShortsWorld, account: Account, delay: u64) {
+    world.propose_wcspr_call(
+        account,
+        "set_config_delay",
+        runtime_args! { "delay" => delay },
+    );
+}
+
+#[when(regex = r"^(.+) proposes replacing the signers with (.+) requiring (\d+) approvals?$")]
+fn propose_signers(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    signers: String,
+    threshold: u32,
+) {
+    let signers: Vec<Address> = accounts(&signers)
+        .into_iter()
+        .map(|signer| world.address(signer))
+        .collect();
+    world.propose_multisig_call(
+        account,
+        "set_signers",
+        runtime_args! { "signers" => signers, "threshold" => threshold },
+    );
+}
+
+#[when(regex = r"^(.+) replaces the signers with (.+) requiring (\d+) approvals? directly$")]
+fn set_signers_directly(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    signers: String,
+    threshold: u32,
+) {
+    world.set_signers_directly(account, accounts(&signers), threshold);
+}
+
+#[when(expr = "{account} approves multisig proposal {int}")]
+fn approve_proposal(world: &mut CasperShortsWorld, account: Account, id: u32) {
+    world.approve_proposal(account, id);
+}
+
+#[when(expr = "{account} revokes the approval of multisig proposal {int}")]
+fn revoke_approval(world: &mut CasperShortsWorld, account: Account, id: u32) {
+    world.revoke_approval(account, id);
+}
+
+#[when(expr = "{account} executes multisig proposal {int}")]
+fn execute_proposal(world: &mut CasperShortsWorld, account: Account, id: u32) {
+    world.execute_proposal(account, id);
+}
+
+#[then(expr = "{int} signer(s) approved multisig proposal {int}")]
+fn check_approvals(world: &mut CasperShortsWorld, count: usize, id: u32) {
+    assert_eq!(world.proposal_approvals(id), count);
+}
+
+#[then(expr = "{int} accounts sign for the multisig")]
+fn check_signers(world: &mut CasperShortsWorld, count: usize) {
+    assert_eq!(world.multisig_signers().len(), count);
+}
+
+#[then(expr = "the flip fee is {int} basis points")]
+fn check_flip_fee(world: &mut CasperShortsWorld, fee: u64) {
+    assert_eq!(world.flip_fee(), fee);
+}
+
+#[then(expr = "the WCSPR config delay is {int}")]
+fn check_wcspr_config_delay(world: &mut CasperShortsWorld, delay: u64) {
+    assert_eq!(world.wcspr_config_delay(), delay);
+}
+
+fn accounts(list: &str) -> Vec<Account> {
+    list.split(',')
+        .flat_map(|part| part.split(" and "))
+        .map(|account| account.trim().parse().unwrap())
+        .collect()
+}
@@ -0,0 +1,84 @@
+use casper_shorts_contracts::orders::{OrderStatus, Trigger};
+use cucumber::{then, when};
+
+use crate::common::{
+    params::{Account, Amount, Price, TokenKind},
+    world::CasperShortsWorld,
+};
+
+// When Alice orders to close 100 LONG when the price falls below 0.008 USD
+#[when(
+    regex = r"^(\w+) orders to close (.+) (LONG|SHORT) when the price (falls below|rises above) (.+) USD$"
+)]
+fn place_order(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    tokens: Amount,
+    token: TokenKind,
+    direction: String,
+    trigger_price: Price,
+) {
+    world.place_order(
+        account,
+        token.side(),
+        tokens.value(),
+        trigger(&direction),
+        trigger_price.value(),
+    );
+}
+
+// When Alice places 10 orders to close 10 LONG when the price falls below 0.008 USD
+#[when(
+    regex = r"^(.+) places (\d+) orders to close (.+) (LONG|SHORT) when the price (falls below|rises above) (.+) USD$"
+)]
+fn place_orders(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    count: u32,
+    tokens: Amount,
+    token: TokenKind,
+    direction: String,
+    trigger_price: Price,
+) {
+    for _ in 0..count {
+        world.place_order(
+            account,
+            token.side(),
+            tokens.value(),
+            trigger(&direction),
+            trigger_price.value(),
+        );
+    }
+}
+
+#[when(expr = "{account} cancels order {int}")]
+fn cancel_order(world: &mut CasperShortsWorld, account: Account, id: u32) {
+    world.cancel_order(account, id);
+}
+
+#[when(expr = "{account} executes at most {int} orders")]
+fn execute_orders(world: &mut CasperShortsWorld, account: Account, max: u32) {
+    world.execute_orders(account, max);
+}
+
+#[then(regex = r"^order (\d+) is (open|executed|cancelled)$")]
+fn check_order_status(world: &mut CasperShortsWorld, id: u32, status: String) {
+    let expected = match status.as_str() {
+        "open" => OrderStatus::Open,
+        "executed" => OrderStatus::Executed,
+        _ => OrderStatus::Cancelled,
+    };
+    assert_eq!(world.get_order(id).status, expected);
+}
+
+#[then(expr = "{int} order(s) of {account} is/are open")]
+fn check_open_orders(world: &mut CasperShortsWorld, count: usize, account: Account) {
+    assert_eq!(world.get_open_orders_of(account).len(), count);
+}
+
+fn trigger(direction: &str) -> Trigger {
+    match direction {
+        "falls below" => Trigger::Below,
+        _ => Trigger::Above,
+    }
+}
@@ -0,0 +1,23 @@
+use cucumber::{then, when};
+
+use crate::common::{params::Account, world::CasperShortsWorld};
+
+#[when(expr = "{account} pauses the market")]
+fn pause(world: &mut CasperShortsWorld, account: Account) {
+    world.set_paused(account, true);
+}
+
+#[when(expr = "{account} unpauses the market")]
+fn unpause(world: &mut CasperShortsWorld, account: Account) {
+    world.set_paused(account, false);
+}
+
+#[then(expr = "the market is paused")]
+fn check_paused(world: &mut CasperShortsWorld) {
+    assert!(world.is_paused());
+}
+
+#[then(expr = "the market is not paused")]
+fn check_not_paused(world: &mut CasperShortsWorld) {
+    assert!(!world.is_paused());
+}
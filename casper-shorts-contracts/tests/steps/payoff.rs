@@ -0,0 +1,24 @@
+use casper_shorts_contracts::system::Payoff;
+use cucumber::{given, then};
+
+use crate::common::world::CasperShortsWorld;
+
+#[given(expr = "a market with a {int}x leveraged payoff")]
+fn leveraged_market(world: &mut CasperShortsWorld, leverage: u64) {
+    *world = CasperShortsWorld::with_market(None, Payoff::LeveragedLinear { leverage });
+}
+
+#[given(expr = "a market with a payoff capped at {int} basis points of gain and {int} of loss")]
+fn capped_market(world: &mut CasperShortsWorld, max_gain: u64, max_loss: u64) {
+    *world = CasperShortsWorld::with_market(None, Payoff::Capped { max_gain, max_loss });
+}
+
+#[given("a market with a power payoff")]
+fn power_market(world: &mut CasperShortsWorld) {
+    *world = CasperShortsWorld::with_market(None, Payoff::Power);
+}
+
+#[then("the market uses the linear payoff")]
+fn check_linear_payoff(world: &mut CasperShortsWorld) {
+    assert_eq!(world.payoff(), Payoff::Linear);
+}
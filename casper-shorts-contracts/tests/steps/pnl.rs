@@ -0,0 +1,61 @@
+use casper_shorts_contracts::pnl::{Pnl, SidePnl};
+use cucumber::then;
+use odra::casper_types::U256;
+
+use crate::common::{
+    params::{Account, Amount, TokenKind},
+    world::CasperShortsWorld,
+};
+
+#[then(expr = "the {token_kind} entry price of {account} is {amount} WCSPR")]
+fn check_entry_price(
+    world: &mut CasperShortsWorld,
+    token: TokenKind,
+    account: Account,
+    price: Amount,
+) {
+    let pnl = side_pnl(world, token, account);
+    assert_close(pnl.entry_price, price.value(), "entry price");
+}
+
+#[then(regex = r"^the (unrealized|realized) (LONG|SHORT) (profit|loss) of (.+) is (.+) WCSPR$")]
+fn check_pnl(
+    world: &mut CasperShortsWorld,
+    kind: String,
+    token: TokenKind,
+    direction: String,
+    account: Account,
+    amount: Amount,
+) {
+    let pnl = side_pnl(world, token, account);
+    let pnl: Pnl = match kind.as_str() {
+        "unrealized" => pnl.unrealized,
+        _ => pnl.realized,
+    };
+    let (actual, other) = match direction.as_str() {
+        "profit" => (pnl.profit, pnl.loss),
+        _ => (pnl.loss, pnl.profit),
+    };
+    assert!(other.is_zero(), "{:?} has {:?}", account, pnl);
+    assert_close(actual, amount.value(), &format!("{} {}", kind, direction));
+}
+
+fn side_pnl(world: &CasperShortsWorld, token: TokenKind, account: Account) -> SidePnl {
+    let report = world.get_pnl(account);
+    match token {
+        TokenKind::LONG => report.long,
+        TokenKind::SHORT => report.short,
+        TokenKind::WCSPR => panic!("WCSPR is not a position"),
+    }
+}
+
+fn assert_close(actual: U256, expected: U256, what: &str) {
+    let diff = actual.abs_diff(expected);
+    assert!(
+        diff < U256::from(10_000),
+        "{} is {} WCSPR but expected {} WCSPR",
+        what,
+        Amount(actual),
+        Amount(expected)
+    );
+}
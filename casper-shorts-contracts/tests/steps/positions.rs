@@ -0,0 +1,55 @@
+use cucumber::then;
+use odra::casper_types::U256;
+
+use crate::common::{
+    params::{Account, Amount, TokenKind},
+    world::CasperShortsWorld,
+};
+
+#[then(expr = "the position of {account} is {amount} {token_kind} worth {amount} WCSPR")]
+fn check_position(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    tokens: Amount,
+    token: TokenKind,
+    value: Amount,
+) {
+    let position = world.get_position(account);
+    let (balance, actual_value) = match token {
+        TokenKind::LONG => (position.long_balance, position.long_value),
+        TokenKind::SHORT => (position.short_balance, position.short_value),
+        TokenKind::WCSPR => panic!("WCSPR is not a position"),
+    };
+    assert_eq!(balance, tokens.value());
+
+    let diff = actual_value.abs_diff(value.value());
+    assert!(
+        diff < U256::from(10_000),
+        "{:?} position is worth {} WCSPR but expected {} WCSPR",
+        account,
+        Amount(actual_value),
+        value
+    );
+}
+
+#[then(expr = "{account} owns {int} basis points of the {token_kind} pool")]
+fn check_pool_share(world: &mut CasperShortsWorld, account: Account, bps: u64, token: TokenKind) {
+    let position = world.get_position(account);
+    let pool_share = match token {
+        TokenKind::LONG => position.long_pool_share,
+        TokenKind::SHORT => position.short_pool_share,
+        TokenKind::WCSPR => panic!("WCSPR is not a position"),
+    };
+    assert_eq!(pool_share, U256::from(bps));
+}
+
+#[then(expr = "{token_kind} share price is {amount} WCSPR")]
+fn check_share_price(world: &mut CasperShortsWorld, token: TokenKind, price: Amount) {
+    let position = world.get_position(Account::Alice);
+    let share_price = match token {
+        TokenKind::LONG => position.long_share_price,
+        TokenKind::SHORT => position.short_share_price,
+        TokenKind::WCSPR => panic!("WCSPR is not a position"),
+    };
+    assert_eq!(share_price, price.value());
+}
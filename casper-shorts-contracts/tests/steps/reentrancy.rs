@@ -0,0 +1,45 @@
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, Amount, TokenKind},
+    reentrant_token::Attack,
+    world::CasperShortsWorld,
+};
+
+#[given("a market wired to reentrant tokens")]
+fn deploy_reentrant_market(world: &mut CasperShortsWorld) {
+    world.deploy_reentrant_market();
+}
+
+#[given(expr = "the reentrant {token_kind} token calls {word} when called")]
+fn arm_reentrant_token(world: &mut CasperShortsWorld, token: TokenKind, entry_point: String) {
+    let attack = match entry_point.as_str() {
+        "nothing" => Attack::None,
+        "deposit_long" => Attack::DepositLong,
+        "withdraw_long" => Attack::WithdrawLong,
+        "deposit_long_from" => Attack::DepositLongFrom,
+        "on_token_transfer" => Attack::OnTokenTransfer,
+        _ => panic!("Unknown attack: {}", entry_point),
+    };
+    world.arm_reentrant_token(token, attack);
+}
+
+#[when(expr = "{account} goes long with {amount} WCSPR on the reentrant market")]
+fn go_long(world: &mut CasperShortsWorld, account: Account, amount: Amount) {
+    world.go_long_on_reentrant_market(account, amount.value());
+}
+
+#[when(expr = "the reentrant market redeems {amount} LONG of {account}")]
+fn withdraw_long(world: &mut CasperShortsWorld, amount: Amount, account: Account) {
+    world.withdraw_long_on_reentrant_market(account, amount.value());
+}
+
+#[then(expr = "{account} holds {amount} {token_kind} of the reentrant market")]
+fn check_balance(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    amount: Amount,
+    token: TokenKind,
+) {
+    assert_eq!(world.reentrant_balance_of(token, account), amount.value());
+}
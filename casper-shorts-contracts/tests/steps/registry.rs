@@ -0,0 +1,56 @@
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, Amount},
+    world::CasperShortsWorld,
+};
+
+#[when(expr = "{account} registers the market with {account} as the price feed")]
+fn register_market(world: &mut CasperShortsWorld, account: Account, price_feed: Account) {
+    world.register_market(account, price_feed);
+}
+
+#[when(expr = "{account} registers a new unconfigured market")]
+fn register_unconfigured_market(world: &mut CasperShortsWorld, account: Account) {
+    world.register_unwired_market(account, false);
+}
+
+#[when(expr = "{account} registers a new market configured only on the Market contract")]
+fn register_partially_configured_market(world: &mut CasperShortsWorld, account: Account) {
+    world.register_unwired_market(account, true);
+}
+
+#[then(expr = "the registry lists {int} market(s)")]
+fn check_market_count(world: &mut CasperShortsWorld, count: u32) {
+    assert_eq!(world.registry_market_count(), count);
+}
+
+#[given("the next market is deployed")]
+fn deploy_next_market(world: &mut CasperShortsWorld) {
+    world.deploy_next_market();
+}
+
+#[when(expr = "{account} proposes the next market on the WCSPR token")]
+fn propose_next_market(world: &mut CasperShortsWorld, account: Account) {
+    world.propose_next_market(account);
+}
+
+#[when(expr = "{account} adds the next market to the WCSPR token")]
+fn add_next_market(world: &mut CasperShortsWorld, account: Account) {
+    world.add_next_market(account);
+}
+
+#[when(expr = "{account} registers the next market")]
+fn register_next_market(world: &mut CasperShortsWorld, account: Account) {
+    world.register_next_market(account);
+}
+
+#[when(expr = "{account} sends {amount} WCSPR to the LONG token of the next market")]
+fn transfer_to_next_long(world: &mut CasperShortsWorld, account: Account, amount: Amount) {
+    world.transfer_to_next_long(account, amount.value());
+}
+
+#[then(expr = "the next market minted {amount} LONG to {account}")]
+fn check_next_long_balance(world: &mut CasperShortsWorld, amount: Amount, account: Account) {
+    assert_eq!(world.next_long_balance(account), amount.value());
+}
@@ -0,0 +1,54 @@
+use cucumber::{given, then, when};
+
+use crate::common::{
+    params::{Account, Amount, TokenKind},
+    world::CasperShortsWorld,
+};
+
+#[given(expr = "the settlement is deferred")]
+fn enable_deferred_settlement(world: &mut CasperShortsWorld) {
+    world.set_deferred_settlement(true);
+}
+
+#[when(expr = "the settlement is immediate again")]
+fn disable_deferred_settlement(world: &mut CasperShortsWorld) {
+    world.set_deferred_settlement(false);
+}
+
+#[when(expr = "{account} claims")]
+fn claim(world: &mut CasperShortsWorld, account: Account) {
+    world.claim(account);
+}
+
+#[when(expr = "{account} queues {int} deposits of {amount} WCSPR")]
+fn queue_deposits(world: &mut CasperShortsWorld, account: Account, count: u32, amount: Amount) {
+    for _ in 0..count {
+        world.go_long(account, amount.value());
+    }
+}
+
+#[then(expr = "the queue holds {int} trade(s) of {account}")]
+fn check_pending_requests(world: &mut CasperShortsWorld, count: usize, account: Account) {
+    assert_eq!(world.pending_requests_of(account).len(), count);
+}
+
+#[then(expr = "{account} can claim {amount} {token_kind}")]
+fn check_claimable(
+    world: &mut CasperShortsWorld,
+    account: Account,
+    amount: Amount,
+    token: TokenKind,
+) {
+    let claimable = world.claimable(account);
+    let claimable = match token {
+        TokenKind::LONG => claimable.long,
+        TokenKind::SHORT => claimable.short,
+        TokenKind::WCSPR => claimable.collateral,
+    };
+    assert_eq!(claimable, amount.value());
+}
+
+#[when(expr = "{account} settles at most {int} queued trades")]
+fn settle_requests(world: &mut CasperShortsWorld, account: Account, max: u32) {
+    world.settle_requests(account, max);
+}
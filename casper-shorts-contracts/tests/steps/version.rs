@@ -0,0 +1,24 @@
+use cucumber::{then, when};
+
+use crate::common::{
+    params::{Account, TokenKind},
+    world::CasperShortsWorld,
+};
+
+#[when(expr = "{account} migrates the Market")]
+fn migrate_market(world: &mut CasperShortsWorld, account: Account) {
+    world.migrate_market(account);
+}
+
+#[when(expr = "{account} migrates the {token_kind} token")]
+fn migrate_token(world: &mut CasperShortsWorld, account: Account, token: TokenKind) {
+    world.migrate_token(account, token);
+}
+
+#[then(expr = "every contract is at state version {int}")]
+fn check_state_versions(world: &mut CasperShortsWorld, version: u32) {
+    assert!(world
+        .state_versions()
+        .iter()
+        .all(|contract_version| *contract_version == version));
+}
@@ -10,4 +10,38 @@ fn main() {
     futures::executor::block_on(CasperShortsWorld::run(
         "tests/features/transfer_interface.feature",
     ));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/slippage.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/preview.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/position.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/pnl.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/orders.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/keepers.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/flip.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/recipients.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/competition.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/limits.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/imbalance.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/insurance.feature"));
+    futures::executor::block_on(CasperShortsWorld::run(
+        "tests/features/deferred_settlement.feature",
+    ));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/early_exit.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/registry.feature"));
+    futures::executor::block_on(CasperShortsWorld::run(
+        "tests/features/config_timelock.feature",
+    ));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/multisig.feature"));
+    futures::executor::block_on(CasperShortsWorld::run(
+        "tests/features/hook_callers.feature",
+    ));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/reentrancy.feature"));
+    futures::executor::block_on(CasperShortsWorld::run(
+        "tests/features/state_version.feature",
+    ));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/expiry.feature"));
+    futures::executor::block_on(CasperShortsWorld::run(
+        "tests/features/global_settlement.feature",
+    ));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/payoff.feature"));
+    futures::executor::block_on(CasperShortsWorld::run("tests/features/pause.feature"));
 }